@@ -1,19 +1,16 @@
-use demand::Confirm;
+use demand::{Confirm, PromptResult};
 
 fn main() {
     let confirm = Confirm::new("Are you sure?")
         .description("This will do a thing.")
         .affirmative("Yes!")
         .negative("No.");
-    match confirm.run() {
-        Ok(confirm) => confirm,
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::Interrupted {
-                println!("{}", e);
-                false
-            } else {
-                panic!("Error: {}", e);
-            }
+    match confirm.run_result() {
+        PromptResult::Value(confirm) => confirm,
+        PromptResult::Cancelled => {
+            println!("user cancelled");
+            false
         }
+        PromptResult::Error(e) => panic!("Error: {}", e),
     };
 }
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+use demand::Input;
+
+fn main() {
+    let input = Input::new("Deploy to production?")
+        .placeholder("type yes to continue")
+        .timeout(Duration::from_secs(5));
+    match input.run() {
+        Ok(value) => println!("got {value:?}"),
+        Err(e) => {
+            if e.kind() == std::io::ErrorKind::Interrupted {
+                println!("{}", e);
+            } else {
+                panic!("Error: {}", e);
+            }
+        }
+    }
+}
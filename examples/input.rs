@@ -27,11 +27,10 @@ fn main() {
         ])
         .validation(notempty_minlen);
     match input.run() {
-        Ok(value) => value,
+        Ok(_) => {}
         Err(e) => {
             if e.kind() == std::io::ErrorKind::Interrupted {
                 println!("{}", e);
-                return;
             } else {
                 panic!("Error: {}", e);
             }
@@ -0,0 +1,20 @@
+use demand::{DemandOption, Input, KeyValue, Select};
+
+fn main() {
+    let name = Input::new("What's your name?")
+        .run()
+        .expect("error running input");
+    let country = Select::new("Country")
+        .option(DemandOption::new("Germany"))
+        .option(DemandOption::new("Japan"))
+        .option(DemandOption::new("Brazil"))
+        .run()
+        .expect("error running select");
+
+    KeyValue::new()
+        .title("Summary")
+        .row("Name", name)
+        .row("Country", country)
+        .run()
+        .expect("error printing summary");
+}
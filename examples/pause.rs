@@ -0,0 +1,13 @@
+use demand::Pause;
+
+fn main() {
+    println!("Step 1 complete.");
+    if let Err(e) = Pause::new("Press any key to continue…").run() {
+        if e.kind() == std::io::ErrorKind::Interrupted {
+            println!("{}", e);
+        } else {
+            panic!("Error: {}", e);
+        }
+    }
+    println!("Step 2 complete.");
+}
@@ -0,0 +1,30 @@
+use std::{sync::mpsc, thread, time::Duration};
+
+use demand::{DemandOption, Select};
+
+fn main() {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for country in ["Germany", "Brazil", "Canada", "Mexico", "France", "Japan"] {
+            thread::sleep(Duration::from_millis(500));
+            if sender.send(DemandOption::new(country)).is_err() {
+                return;
+            }
+        }
+    });
+
+    let ms = Select::new("Country")
+        .description("Countries are loaded in the background")
+        .filterable(true)
+        .stream(receiver);
+    match ms.run() {
+        Ok(_) => {}
+        Err(e) => {
+            if e.kind() == std::io::ErrorKind::Interrupted {
+                println!("{}", e);
+            } else {
+                panic!("Error: {}", e);
+            }
+        }
+    };
+}
@@ -15,7 +15,7 @@ fn main() {
                     true => Ok(()),
                     false => Err("ew stinky 'j' not welcome here"),
                 })
-                .theme(&Theme::catppuccin())
+                .theme(Theme::catppuccin())
                 .placeholder("Words go here")
                 .run()
                 .unwrap();
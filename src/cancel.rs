@@ -0,0 +1,109 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use console::{Key, Term};
+
+use crate::poll;
+
+/// How often a prompt waiting for a key checks whether it was cancelled
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A handle for cancelling running prompts from another thread
+///
+/// Clones share the same state, so a prompt given one clone with `cancel_token` is cancelled when
+/// any other clone is. A cancelled prompt returns an `Interrupted` error, like one cancelled with
+/// ctrl+c. On Windows the token is only checked between key presses.
+///
+/// # Example
+/// ```rust,no_run
+/// use demand::{CancelToken, Confirm};
+/// use std::{thread, time::Duration};
+///
+/// let token = CancelToken::new();
+/// let timeout = token.clone();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_secs(10));
+///     timeout.cancel();
+/// });
+/// let confirm = Confirm::new("Continue?").cancel_token(token);
+/// let result = confirm.run();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every prompt holding a clone of this token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether `cancel` has been called on this token or one of its clones
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Read a key from `term`, failing with `Interrupted` once `token` is cancelled
+pub(crate) fn read_key(term: &Term, token: Option<&CancelToken>) -> io::Result<Key> {
+    if token.is_none() {
+        return term.read_key();
+    }
+    loop {
+        if let Some(key) = read_key_timeout(term, token, POLL_INTERVAL)? {
+            return Ok(key);
+        }
+    }
+}
+
+/// Read a key from `term`, waiting at most `timeout` and failing with `Interrupted` once `token`
+/// is cancelled
+pub(crate) fn read_key_timeout(
+    term: &Term,
+    token: Option<&CancelToken>,
+    timeout: Duration,
+) -> io::Result<Option<Key>> {
+    let Some(token) = token else {
+        return poll::read_key_timeout(term, timeout);
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        if token.is_cancelled() {
+            term.show_cursor()?;
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "prompt cancelled",
+            ));
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+        if let Some(key) = poll::read_key_timeout(term, remaining.min(POLL_INTERVAL))? {
+            return Ok(Some(key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+
+        let err = read_key(&Term::stderr(), Some(&token)).unwrap_err();
+        assert_eq!(io::ErrorKind::Interrupted, err.kind());
+    }
+}
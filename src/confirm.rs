@@ -1,11 +1,16 @@
+use std::borrow::Cow;
 use std::io;
-use std::io::Write;
+use std::io::{BufRead, Write};
+use std::sync::mpsc::Sender;
 
 use console::{Key, Term};
 use termcolor::{Buffer, WriteColor};
 
+use crate::cancel::{self, CancelToken};
+use crate::layout::Layout;
+use crate::outcome::{self, Outcome, SuccessFormatFn};
 use crate::theme::Theme;
-use crate::{ctrlc, theme};
+use crate::{ctrlc, event, help, term, theme, Input, PromptEvent, PromptResult};
 
 /// Select multiple options from a list
 ///
@@ -32,7 +37,7 @@ pub struct Confirm<'a> {
     /// The title of the selector
     pub title: String,
     /// The colors/style of the selector
-    pub theme: &'a Theme,
+    pub theme: Cow<'a, Theme>,
     /// A description to display above the selector
     pub description: String,
     /// The text to display for the affirmative option
@@ -42,7 +47,20 @@ pub struct Confirm<'a> {
     /// If true, the affirmative option is selected by default
     pub selected: bool,
     term: Term,
+    cancel_token: Option<CancelToken>,
     height: usize,
+    help_overlay: bool,
+    back_hint: bool,
+    showing_help: bool,
+    observer: Option<Sender<PromptEvent>>,
+    success_format: Option<SuccessFormatFn<'a>>,
+    tee: Option<Box<dyn Write + 'a>>,
+    preserve_below: bool,
+    clear_screen: bool,
+    layout: Layout,
+    phrase: Option<String>,
+    affirmative_keys: Vec<char>,
+    negative_keys: Vec<char>,
 }
 
 impl<'a> Confirm<'a> {
@@ -51,12 +69,25 @@ impl<'a> Confirm<'a> {
         Self {
             title: title.into(),
             description: String::new(),
-            theme: &*theme::DEFAULT,
-            term: Term::stderr(),
+            theme: Cow::Owned(theme::default_theme()),
+            term: term::default_term(),
+            cancel_token: None,
             affirmative: "Yes".to_string(),
             negative: "No".to_string(),
             selected: true,
             height: 0,
+            help_overlay: false,
+            back_hint: false,
+            showing_help: false,
+            observer: None,
+            success_format: None,
+            tee: None,
+            preserve_below: false,
+            clear_screen: false,
+            layout: Layout::default(),
+            phrase: None,
+            affirmative_keys: vec![],
+            negative_keys: vec![],
         }
     }
 
@@ -78,45 +109,151 @@ impl<'a> Confirm<'a> {
         self
     }
 
+    /// Accept `keys` for the affirmative option in addition to the first letter of its label,
+    /// e.g. `j` for "ja"
+    pub fn affirmative_keys(mut self, keys: &[char]) -> Self {
+        self.affirmative_keys = keys.iter().flat_map(|c| c.to_lowercase()).collect();
+        self
+    }
+
+    /// Accept `keys` for the negative option in addition to the first letter of its label
+    pub fn negative_keys(mut self, keys: &[char]) -> Self {
+        self.negative_keys = keys.iter().flat_map(|c| c.to_lowercase()).collect();
+        self
+    }
+
     /// Set whether the affirmative option is selected by default
     pub fn selected(mut self, selected: bool) -> Self {
         self.selected = selected;
         self
     }
 
+    /// Require typing `phrase` to confirm instead of choosing between the two options
+    ///
+    /// `run` returns true only if the typed text matches `phrase` exactly, and false otherwise.
+    pub fn require_phrase(mut self, phrase: &str) -> Self {
+        self.phrase = Some(phrase.to_string());
+        self
+    }
+
+    /// Report the outcome of the prompt to `observer` in addition to returning it
+    pub fn observe(mut self, observer: Sender<PromptEvent>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Set whether content printed below the prompt is kept on submit instead of being cleared
+    pub fn preserve_below(mut self, preserve_below: bool) -> Self {
+        self.preserve_below = preserve_below;
+        self
+    }
+
+    /// Set whether the whole terminal is cleared before each redraw
+    ///
+    /// By default only the lines drawn by the prompt are cleared, so output above it is kept.
+    pub fn clear_screen(mut self, clear_screen: bool) -> Self {
+        self.clear_screen = clear_screen;
+        self
+    }
+
+    /// Set whether pressing `?` shows an overlay listing every keybinding
+    pub fn enable_help_overlay(mut self, help_overlay: bool) -> Self {
+        self.help_overlay = help_overlay;
+        self
+    }
+
+    /// Set whether to show an "esc back" hint, for flows that step back when the prompt is cancelled
+    pub fn back_hint(mut self, back_hint: bool) -> Self {
+        self.back_hint = back_hint;
+        self
+    }
+
+    /// Set the maximum width of the prompt, wrapping longer lines
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.layout.max_width = Some(max_width);
+        self
+    }
+
+    /// Set whether the prompt is centered horizontally in the terminal
+    pub fn center(mut self, center: bool) -> Self {
+        self.layout.center = center;
+        self
+    }
+
+    /// Cancel the confirm with an `Interrupted` error once `token` is cancelled, e.g. from another
+    /// thread
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
     /// Set the theme of the dialog
-    pub fn theme(mut self, theme: &'a Theme) -> Self {
-        self.theme = theme;
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
         self
     }
 
+    /// Set how the line shown after submitting is formatted, replacing the default "Title value"
+    ///
+    /// Return an empty string to show nothing.
+    pub fn success_format(mut self, format: impl Fn(&Outcome) -> String + 'a) -> Self {
+        self.success_format = Some(Box::new(format));
+        self
+    }
+
+    /// Also write the line shown after submitting to `sink`, without colors
+    ///
+    /// Errors writing to `sink` are ignored.
+    pub fn tee(mut self, sink: Box<dyn Write + 'a>) -> Self {
+        self.tee = Some(sink);
+        self
+    }
+
+    /// Displays the prompt like `run`, returning whether it was submitted, cancelled or failed
+    pub fn run_result(self) -> PromptResult<bool> {
+        self.run().into()
+    }
+
     /// Displays the dialog to the user and returns their response
     ///
     /// This function will block until the user submits the input. If the user cancels the input,
     /// an error of type `io::ErrorKind::Interrupted` is returned.
+    ///
+    /// When stderr is not a terminal, the title is printed with a hint such as `[Y/n]`, where the
+    /// default is uppercase, and a line is read from stdin instead. It may be either label or
+    /// the key of either option, and an empty line picks the default. Other input returns an
+    /// error of type `io::ErrorKind::InvalidInput`.
     pub fn run(mut self) -> io::Result<bool> {
+        if let Some(phrase) = self.phrase.take() {
+            return self.run_phrase(&phrase);
+        }
+        if !self.term.is_term() {
+            let mut term = self.term.clone();
+            return self.read_non_tty(&mut io::stdin().lock(), &mut term);
+        }
         let ctrlc_handle = ctrlc::show_cursor_after_ctrlc(&self.term)?;
 
-        let affirmative_char = self.affirmative.to_lowercase().chars().next().unwrap();
-        let negative_char = self.negative.to_lowercase().chars().next().unwrap();
         self.term.clear_line()?;
         self.term.hide_cursor()?;
         loop {
             self.clear()?;
-            let output = self.render()?;
+            let output = self
+                .layout
+                .apply(self.render()?, self.term.size().1 as usize);
             self.height = output.lines().count() - 1;
             self.term.write_all(output.as_bytes())?;
             self.term.flush()?;
-            match self.term.read_key()? {
+            let key = cancel::read_key(&self.term, self.cancel_token.as_ref())?;
+            if self.showing_help {
+                self.showing_help = false;
+                continue;
+            }
+            match key {
                 Key::ArrowLeft | Key::Char('h') => self.handle_left(),
                 Key::ArrowRight | Key::Char('l') => self.handle_right(),
-                Key::Char(c) if c == affirmative_char => {
-                    self.selected = true;
-                    ctrlc_handle.close();
-                    return self.handle_submit();
-                }
-                Key::Char(c) if c == negative_char => {
-                    self.selected = false;
+                Key::Char('?') if self.help_overlay => self.showing_help = true,
+                Key::Char(c) if self.key_choice(c).is_some() => {
+                    self.selected = self.key_choice(c).unwrap();
                     ctrlc_handle.close();
                     return self.handle_submit();
                 }
@@ -127,6 +264,7 @@ impl<'a> Confirm<'a> {
                 Key::Escape => {
                     self.term.show_cursor()?;
                     ctrlc_handle.close();
+                    event::cancelled(&self.observer, &self.title);
                     return Err(io::Error::new(io::ErrorKind::Interrupted, "user cancelled"));
                 }
                 _ => {}
@@ -134,12 +272,111 @@ impl<'a> Confirm<'a> {
         }
     }
 
+    fn run_phrase(self, phrase: &str) -> io::Result<bool> {
+        let description = self.phrase_description(phrase);
+        let mut input = Input::new(self.title)
+            .description(&description)
+            .theme(self.theme);
+        if let Some(observer) = self.observer {
+            input = input.observe(observer);
+        }
+        if let Some(format) = self.success_format {
+            input = input.success_format(format);
+        }
+        if let Some(sink) = self.tee {
+            input = input.tee(sink);
+        }
+        Ok(input.run()? == phrase)
+    }
+
+    fn phrase_description(&self, phrase: &str) -> String {
+        let prompt = format!("Type \"{phrase}\" to confirm");
+        match self.description.is_empty() {
+            true => prompt,
+            false => format!("{}\n{prompt}", self.description),
+        }
+    }
+
+    /// Prints the title with a hint and reads the answer from `input`
+    fn read_non_tty(self, input: &mut dyn BufRead, output: &mut dyn Write) -> io::Result<bool> {
+        let mut prompt = self.title.clone();
+        if !self.description.is_empty() {
+            prompt.push_str(&format!("\n{}", self.description));
+        }
+        write!(output, "{prompt} {} ", self.hint())?;
+        output.flush()?;
+
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        let answer = line.trim().to_lowercase();
+        let mut chars = answer.chars();
+        let selected = match (chars.next(), chars.next()) {
+            (None, _) => Some(self.selected),
+            _ if answer == self.affirmative.to_lowercase() => Some(true),
+            _ if answer == self.negative.to_lowercase() => Some(false),
+            (Some(c), None) => self.key_choice(c),
+            _ => None,
+        };
+        let Some(selected) = selected else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid answer: {}", line.trim()),
+            ));
+        };
+        let value = match selected {
+            true => &self.affirmative,
+            false => &self.negative,
+        };
+        event::submitted(&self.observer, &self.title, value);
+        Ok(selected)
+    }
+
+    /// The option keys with the default uppercase, e.g. `[Y/n]`
+    fn hint(&self) -> String {
+        let key = |label: &str, default: bool| {
+            let c = label.chars().next().unwrap_or(' ');
+            match default {
+                true => c.to_uppercase().to_string(),
+                false => c.to_lowercase().to_string(),
+            }
+        };
+        format!(
+            "[{}/{}]",
+            key(&self.affirmative, self.selected),
+            key(&self.negative, !self.selected)
+        )
+    }
+
+    /// The option key `c` picks, `true` for affirmative, if any
+    fn key_choice(&self, c: char) -> Option<bool> {
+        let c = c.to_lowercase().next().unwrap_or(c);
+        let first = |label: &str| label.to_lowercase().chars().next();
+        if first(&self.affirmative) == Some(c) || self.affirmative_keys.contains(&c) {
+            Some(true)
+        } else if first(&self.negative) == Some(c) || self.negative_keys.contains(&c) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
     fn handle_submit(mut self) -> io::Result<bool> {
-        self.term.clear_to_end_of_screen()?;
+        if !self.preserve_below {
+            self.term.clear_to_end_of_screen()?;
+        }
         self.clear()?;
         self.term.show_cursor()?;
-        let output = self.render_success()?;
+        let output = self
+            .layout
+            .apply(self.render_success()?, self.term.size().1 as usize);
         self.term.write_all(output.as_bytes())?;
+        term::record_height(&self.term, &output);
+        term::tee(&mut self.tee, &output);
+        let value = match self.selected {
+            true => &self.affirmative,
+            false => &self.negative,
+        };
+        event::submitted(&self.observer, &self.title, value);
         Ok(self.selected)
     }
 
@@ -156,6 +393,20 @@ impl<'a> Confirm<'a> {
     }
 
     fn render(&self) -> io::Result<String> {
+        let affirmative_char = self.affirmative.to_lowercase().chars().next().unwrap();
+        let negative_char = self.negative.to_lowercase().chars().next().unwrap();
+        if self.showing_help {
+            let affirmative_key = affirmative_char.to_string();
+            let negative_key = negative_char.to_string();
+            let keys = [
+                ("←/→/h/l", "toggle"),
+                (affirmative_key.as_str(), self.affirmative.as_str()),
+                (negative_key.as_str(), self.negative.as_str()),
+                ("enter", "submit"),
+                ("esc", "cancel"),
+            ];
+            return help::render_overlay(&self.theme, &self.title, &keys);
+        }
         let mut out = Buffer::ansi();
 
         out.set_color(&self.theme.title)?;
@@ -186,20 +437,22 @@ impl<'a> Confirm<'a> {
         writeln!(out, "\n")?;
 
         let mut help_keys = vec![("←/→", "toggle")];
-        let affirmative_char = self.affirmative.to_lowercase().chars().next().unwrap();
-        let negative_char = self.negative.to_lowercase().chars().next().unwrap();
         let submit_keys = format!("{affirmative_char}/{negative_char}/enter");
         help_keys.push((&submit_keys, "submit"));
-        for (i, (key, desc)) in help_keys.iter().enumerate() {
-            if i > 0 {
-                out.set_color(&self.theme.help_sep)?;
-                write!(out, " • ")?;
-            }
-            out.set_color(&self.theme.help_key)?;
-            write!(out, "{}", key)?;
-            out.set_color(&self.theme.help_desc)?;
-            write!(out, " {}", desc)?;
+        if self.help_overlay {
+            help_keys.push(("?", "help"));
+        }
+        if self.back_hint {
+            help_keys.push(("esc", "back"));
         }
+        help::render_keys(
+            &mut out,
+            &self.theme,
+            &help_keys,
+            false,
+            0,
+            self.term.size().1 as usize,
+        )?;
         writeln!(out)?;
 
         out.reset()?;
@@ -207,6 +460,16 @@ impl<'a> Confirm<'a> {
     }
 
     fn render_success(&self) -> io::Result<String> {
+        if let Some(format) = &self.success_format {
+            let outcome = Outcome {
+                title: &self.title,
+                values: vec![match self.selected {
+                    true => &self.affirmative,
+                    false => &self.negative,
+                }],
+            };
+            return Ok(outcome::render(format, &outcome));
+        }
         let mut out = Buffer::ansi();
         out.set_color(&self.theme.title)?;
         write!(out, "{}", self.title)?;
@@ -221,7 +484,11 @@ impl<'a> Confirm<'a> {
     }
 
     fn clear(&mut self) -> io::Result<()> {
-        self.term.clear_last_lines(self.height)?;
+        if self.clear_screen {
+            self.term.clear_screen()?;
+        } else {
+            self.term.clear_last_lines(self.height)?;
+        }
         self.height = 0;
         Ok(())
     }
@@ -253,4 +520,75 @@ mod tests {
             without_ansi(confirm.render().unwrap().as_str())
         );
     }
+
+    #[test]
+    fn test_back_hint() {
+        let confirm = Confirm::new("Are you sure?")
+            .description("This will do a thing.")
+            .back_hint(true);
+
+        assert_eq!(
+            indoc! {
+              "Are you sure?
+             This will do a thing.
+
+                Yes     No  
+
+             ←/→ toggle • y/n/enter submit • esc back
+            "
+            },
+            without_ansi(confirm.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn test_phrase_description() {
+        let confirm = Confirm::new("Delete the database?").require_phrase("delete production");
+        assert_eq!(
+            "Type \"delete production\" to confirm",
+            confirm.phrase_description("delete production")
+        );
+        let confirm = confirm.description("This cannot be undone.");
+        assert_eq!(
+            "This cannot be undone.\nType \"delete production\" to confirm",
+            confirm.phrase_description("delete production")
+        );
+    }
+
+    #[test]
+    fn test_key_choice() {
+        let confirm = Confirm::new("Fortfahren?")
+            .affirmative("Ja")
+            .negative("Nein")
+            .affirmative_keys(&['y'])
+            .negative_keys(&['X']);
+        assert_eq!(Some(true), confirm.key_choice('j'));
+        assert_eq!(Some(true), confirm.key_choice('Y'));
+        assert_eq!(Some(false), confirm.key_choice('n'));
+        assert_eq!(Some(false), confirm.key_choice('x'));
+        assert_eq!(None, confirm.key_choice('q'));
+    }
+
+    #[test]
+    fn test_non_tty() {
+        let confirm = || Confirm::new("Continue?").affirmative("Yes").negative("No");
+        assert_eq!("[Y/n]", confirm().hint());
+        assert_eq!("[y/N]", confirm().selected(false).hint());
+
+        let mut output = vec![];
+        let answer = confirm()
+            .selected(false)
+            .read_non_tty(&mut "\n".as_bytes(), &mut output)
+            .unwrap();
+        assert!(!answer);
+        assert_eq!("Continue? [y/N] ", String::from_utf8(output).unwrap());
+        for (line, expected) in [("yes\n", true), ("N\n", false), ("y\n", true)] {
+            let answer = confirm().read_non_tty(&mut line.as_bytes(), &mut io::sink());
+            assert_eq!(expected, answer.unwrap());
+        }
+        let err = confirm()
+            .read_non_tty(&mut "maybe\n".as_bytes(), &mut io::sink())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
 }
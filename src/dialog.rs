@@ -1,11 +1,16 @@
+use std::borrow::Cow;
 use std::io;
 use std::io::Write;
+use std::sync::mpsc::Sender;
 
 use console::{Key, Term};
 use termcolor::{Buffer, WriteColor};
 
+use crate::cancel::{self, CancelToken};
+use crate::layout::Layout;
+use crate::outcome::{self, Outcome, SuccessFormatFn};
 use crate::theme::Theme;
-use crate::{ctrlc, theme};
+use crate::{ctrlc, event, help, term, theme, PromptEvent, PromptResult};
 
 #[derive(Clone, Debug, Default, PartialEq)]
 /// A button to select in a dialog
@@ -61,15 +66,24 @@ pub struct Dialog<'a> {
     /// The title of the selector
     pub title: String,
     /// The colors/style of the selector
-    pub theme: &'a Theme,
+    pub theme: Cow<'a, Theme>,
     /// A description to display above the selector
     pub description: String,
     /// The buttons to display to the user
     pub buttons: Vec<DialogButton>,
 
     term: Term,
+    cancel_token: Option<CancelToken>,
     height: usize,
     selected_button_idx: usize,
+    help_overlay: bool,
+    back_hint: bool,
+    showing_help: bool,
+    observer: Option<Sender<PromptEvent>>,
+    success_format: Option<SuccessFormatFn<'a>>,
+    tee: Option<Box<dyn Write + 'a>>,
+    clear_screen: bool,
+    layout: Layout,
 }
 
 impl<'a> Dialog<'a> {
@@ -80,11 +94,20 @@ impl<'a> Dialog<'a> {
         Self {
             title: title.into(),
             description: String::new(),
-            theme: &*theme::DEFAULT,
-            term: Term::stderr(),
+            theme: Cow::Owned(theme::default_theme()),
+            term: term::default_term(),
+            cancel_token: None,
             buttons: vec![DialogButton::new("Ok"), DialogButton::new("Cancel")],
             height: 0,
             selected_button_idx: 0,
+            help_overlay: false,
+            back_hint: false,
+            showing_help: false,
+            observer: None,
+            success_format: None,
+            tee: None,
+            clear_screen: false,
+            layout: Layout::default(),
         }
     }
 
@@ -118,12 +141,78 @@ impl<'a> Dialog<'a> {
         self
     }
 
+    /// Report the outcome of the prompt to `observer` in addition to returning it
+    pub fn observe(mut self, observer: Sender<PromptEvent>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Set whether the whole terminal is cleared before each redraw
+    ///
+    /// By default only the lines drawn by the dialog are cleared, so output above it is kept.
+    pub fn clear_screen(mut self, clear_screen: bool) -> Self {
+        self.clear_screen = clear_screen;
+        self
+    }
+
+    /// Set whether pressing `?` shows an overlay listing every keybinding
+    pub fn enable_help_overlay(mut self, help_overlay: bool) -> Self {
+        self.help_overlay = help_overlay;
+        self
+    }
+
+    /// Set whether to show an "esc back" hint, for flows that step back when the prompt is cancelled
+    pub fn back_hint(mut self, back_hint: bool) -> Self {
+        self.back_hint = back_hint;
+        self
+    }
+
+    /// Set the maximum width of the dialog, wrapping longer lines
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.layout.max_width = Some(max_width);
+        self
+    }
+
+    /// Set whether the dialog is centered horizontally in the terminal
+    pub fn center(mut self, center: bool) -> Self {
+        self.layout.center = center;
+        self
+    }
+
+    /// Cancel the dialog with an `Interrupted` error once `token` is cancelled, e.g. from another
+    /// thread
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
     /// Set the theme of the dialog
-    pub fn theme(mut self, theme: &'a Theme) -> Self {
-        self.theme = theme;
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Set how the line shown after submitting is formatted, replacing the default "Title value"
+    ///
+    /// Return an empty string to show nothing.
+    pub fn success_format(mut self, format: impl Fn(&Outcome) -> String + 'a) -> Self {
+        self.success_format = Some(Box::new(format));
         self
     }
 
+    /// Also write the line shown after submitting to `sink`, without colors
+    ///
+    /// Errors writing to `sink` are ignored.
+    pub fn tee(mut self, sink: Box<dyn Write + 'a>) -> Self {
+        self.tee = Some(sink);
+        self
+    }
+
+    /// Displays the dialog like `run`, returning whether it was submitted, cancelled or failed
+    pub fn run_result(self) -> PromptResult<String> {
+        self.run().into()
+    }
+
     /// Displays the dialog to the user and returns their response.
     ///
     /// The response will be the label of the selected button.
@@ -136,13 +225,21 @@ impl<'a> Dialog<'a> {
         self.term.hide_cursor()?;
         loop {
             self.clear()?;
-            let output = self.render()?;
+            let output = self
+                .layout
+                .apply(self.render()?, self.term.size().1 as usize);
             self.height = output.lines().count() - 1;
             self.term.write_all(output.as_bytes())?;
             self.term.flush()?;
-            match self.term.read_key()? {
+            let key = cancel::read_key(&self.term, self.cancel_token.as_ref())?;
+            if self.showing_help {
+                self.showing_help = false;
+                continue;
+            }
+            match key {
                 Key::ArrowLeft | Key::Char('h') => self.handle_left(),
                 Key::ArrowRight | Key::Char('l') => self.handle_right(),
+                Key::Char('?') if self.help_overlay => self.showing_help = true,
                 Key::Char(c) if self.buttons.iter().any(|b| b.key == c) => {
                     self.selected_button_idx =
                         self.buttons.iter().position(|b| b.key == c).unwrap();
@@ -156,6 +253,7 @@ impl<'a> Dialog<'a> {
                 Key::Escape => {
                     self.term.show_cursor()?;
                     ctrlc_handle.close();
+                    event::cancelled(&self.observer, &self.title);
                     return Err(io::Error::new(io::ErrorKind::Interrupted, "user cancelled"));
                 }
                 _ => {}
@@ -166,13 +264,18 @@ impl<'a> Dialog<'a> {
     fn handle_submit(mut self) -> io::Result<String> {
         self.clear()?;
         self.term.show_cursor()?;
-        let output = self.render_success()?;
+        let output = self
+            .layout
+            .apply(self.render_success()?, self.term.size().1 as usize);
         self.term.write_all(output.as_bytes())?;
+        term::record_height(&self.term, &output);
+        term::tee(&mut self.tee, &output);
         let result = if !self.buttons.is_empty() {
             self.buttons[self.selected_button_idx].label.clone()
         } else {
             "".to_string()
         };
+        event::submitted(&self.observer, &self.title, &result);
         Ok(result)
     }
 
@@ -186,6 +289,20 @@ impl<'a> Dialog<'a> {
     }
 
     fn render(&self) -> io::Result<String> {
+        if self.showing_help {
+            let button_keys = self
+                .buttons
+                .iter()
+                .map(|b| b.key.to_string())
+                .collect::<Vec<_>>();
+            let mut keys = vec![("←/→/h/l", "toggle")];
+            for (button, key) in self.buttons.iter().zip(&button_keys) {
+                keys.push((key.as_str(), button.label.as_str()));
+            }
+            keys.push(("enter", "submit"));
+            keys.push(("esc", "cancel"));
+            return help::render_overlay(&self.theme, &self.title, &keys);
+        }
         let mut out = Buffer::ansi();
 
         out.set_color(&self.theme.title)?;
@@ -223,16 +340,20 @@ impl<'a> Dialog<'a> {
             });
         let submit_keys = format!("{}enter", button_keys);
         help_keys.push((&submit_keys, "submit"));
-        for (i, (key, desc)) in help_keys.iter().enumerate() {
-            if i > 0 {
-                out.set_color(&self.theme.help_sep)?;
-                write!(out, " • ")?;
-            }
-            out.set_color(&self.theme.help_key)?;
-            write!(out, "{}", key)?;
-            out.set_color(&self.theme.help_desc)?;
-            write!(out, " {}", desc)?;
+        if self.help_overlay {
+            help_keys.push(("?", "help"));
         }
+        if self.back_hint {
+            help_keys.push(("esc", "back"));
+        }
+        help::render_keys(
+            &mut out,
+            &self.theme,
+            &help_keys,
+            false,
+            0,
+            self.term.size().1 as usize,
+        )?;
         writeln!(out)?;
 
         out.reset()?;
@@ -240,6 +361,18 @@ impl<'a> Dialog<'a> {
     }
 
     fn render_success(&self) -> io::Result<String> {
+        if let Some(format) = &self.success_format {
+            let outcome = Outcome {
+                title: &self.title,
+                values: self
+                    .buttons
+                    .get(self.selected_button_idx)
+                    .map(|b| b.label.as_str())
+                    .into_iter()
+                    .collect(),
+            };
+            return Ok(outcome::render(format, &outcome));
+        }
         let mut out = Buffer::ansi();
         out.set_color(&self.theme.title)?;
         write!(out, "{}", self.title)?;
@@ -258,8 +391,11 @@ impl<'a> Dialog<'a> {
     }
 
     fn clear(&mut self) -> io::Result<()> {
-        self.term.clear_to_end_of_screen()?;
-        self.term.clear_last_lines(self.height)?;
+        if self.clear_screen {
+            self.term.clear_screen()?;
+        } else {
+            self.term.clear_last_lines(self.height)?;
+        }
         self.height = 0;
         Ok(())
     }
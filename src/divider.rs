@@ -0,0 +1,123 @@
+use std::borrow::Cow;
+use std::io;
+use std::io::Write;
+
+use console::Term;
+use termcolor::{Buffer, WriteColor};
+
+use crate::theme::Theme;
+use crate::{term, theme};
+
+/// A horizontal rule with an optional centered label
+///
+/// # Example
+/// ```rust
+/// use demand::Divider;
+///
+/// Divider::new().label("Options").width(40).run().unwrap();
+/// ```
+pub struct Divider<'a> {
+    /// The label to center in the rule
+    pub label: String,
+    /// The colors/style of the divider
+    pub theme: Cow<'a, Theme>,
+    /// The character the rule is drawn with
+    pub rule: char,
+
+    width: Option<usize>,
+    term: Term,
+}
+
+impl<'a> Divider<'a> {
+    /// Create a new divider spanning the terminal width
+    pub fn new() -> Self {
+        Self {
+            label: String::new(),
+            theme: Cow::Owned(theme::default_theme()),
+            rule: if theme::ascii() { '-' } else { '─' },
+            width: None,
+            term: term::default_term(),
+        }
+    }
+
+    /// Set the label centered in the rule
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+
+    /// Set the width of the divider. Defaults to the terminal width.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set the character the rule is drawn with
+    pub fn rule(mut self, rule: char) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Set the theme of the divider
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Prints the divider
+    pub fn run(mut self) -> io::Result<()> {
+        let output = self.render()?;
+        self.term.write_all(output.as_bytes())?;
+        self.term.flush()
+    }
+
+    fn render(&self) -> io::Result<String> {
+        let width = self.width.unwrap_or_else(|| self.term.size().1 as usize);
+        let mut out = Buffer::ansi();
+
+        if self.label.is_empty() {
+            out.set_color(&self.theme.description)?;
+            writeln!(out, "{}", self.rule.to_string().repeat(width))?;
+        } else {
+            let label_width = console::measure_text_width(&self.label) + 2;
+            let left = width.saturating_sub(label_width) / 2;
+            let right = width.saturating_sub(label_width + left);
+            out.set_color(&self.theme.description)?;
+            write!(out, "{} ", self.rule.to_string().repeat(left))?;
+            out.set_color(&self.theme.title)?;
+            write!(out, "{}", self.label)?;
+            out.set_color(&self.theme.description)?;
+            writeln!(out, " {}", self.rule.to_string().repeat(right))?;
+        }
+
+        out.reset()?;
+        Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
+    }
+}
+
+impl Default for Divider<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::without_ansi;
+
+    #[test]
+    fn test_render() {
+        let divider = Divider::new().width(10);
+        assert_eq!(
+            "──────────\n",
+            without_ansi(divider.render().unwrap().as_str())
+        );
+
+        let divider = Divider::new().label("Options").width(15);
+        assert_eq!(
+            "─── Options ───\n",
+            without_ansi(divider.render().unwrap().as_str())
+        );
+    }
+}
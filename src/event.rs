@@ -0,0 +1,39 @@
+use std::sync::mpsc::Sender;
+
+/// The outcome of a prompt, reported to a channel registered with `observe`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PromptEvent {
+    /// The user submitted the prompt
+    Submitted {
+        /// Title of the prompt
+        title: String,
+        /// The submitted value as displayed to the user
+        value: String,
+    },
+    /// The user cancelled the prompt
+    Cancelled {
+        /// Title of the prompt
+        title: String,
+    },
+}
+
+/// Report a submission to the observer, if any
+///
+/// A disconnected observer is ignored so it never affects the prompt itself.
+pub(crate) fn submitted(observer: &Option<Sender<PromptEvent>>, title: &str, value: &str) {
+    if let Some(observer) = observer {
+        let _ = observer.send(PromptEvent::Submitted {
+            title: title.to_string(),
+            value: value.to_string(),
+        });
+    }
+}
+
+/// Report a cancellation to the observer, if any
+pub(crate) fn cancelled(observer: &Option<Sender<PromptEvent>>, title: &str) {
+    if let Some(observer) = observer {
+        let _ = observer.send(PromptEvent::Cancelled {
+            title: title.to_string(),
+        });
+    }
+}
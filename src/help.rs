@@ -0,0 +1,136 @@
+use std::io;
+use std::io::Write;
+
+use console::Key;
+use termcolor::{Buffer, WriteColor};
+
+use crate::{theme, Theme};
+
+/// The name of `key` as shown in help keys, e.g. `enter` or `ctrl+s`
+pub(crate) fn key_name(key: &Key) -> String {
+    match key {
+        Key::Enter => "enter".to_string(),
+        Key::Tab => "tab".to_string(),
+        Key::BackTab => "shift+tab".to_string(),
+        Key::Backspace => "backspace".to_string(),
+        Key::Del => "delete".to_string(),
+        Key::Home => "home".to_string(),
+        Key::End => "end".to_string(),
+        Key::PageUp => "pgup".to_string(),
+        Key::PageDown => "pgdown".to_string(),
+        Key::Char(' ') => "space".to_string(),
+        Key::Char(c) if c.is_ascii_control() => {
+            format!("ctrl+{}", ((*c as u8) | 0x60) as char)
+        }
+        Key::Char(c) => c.to_string(),
+        key => format!("{key:?}").to_lowercase(),
+    }
+}
+
+/// Write help keys separated by the theme's separator, wrapping onto new lines past `width`
+///
+/// `start_col` is the width of anything already written on the line, and `leading_sep` puts a
+/// separator before the first key.
+pub(crate) fn render_keys(
+    out: &mut Buffer,
+    theme: &Theme,
+    keys: &[(&str, &str)],
+    leading_sep: bool,
+    start_col: usize,
+    width: usize,
+) -> io::Result<()> {
+    let sep_width = console::measure_text_width(&theme.help_sep_str);
+    let mut col = start_col;
+    for (i, (key, desc)) in keys.iter().enumerate() {
+        let key = theme::help_key(key);
+        let key_width = console::measure_text_width(&key) + 1 + console::measure_text_width(desc);
+        let sep = i > 0 || leading_sep;
+        if col > 0 && col + sep_width * sep as usize + key_width > width {
+            writeln!(out)?;
+            col = 0;
+        } else if sep {
+            out.set_color(&theme.help_sep)?;
+            write!(out, "{}", theme.help_sep_str)?;
+            col += sep_width;
+        }
+        out.set_color(&theme.help_key)?;
+        write!(out, "{}", key)?;
+        out.set_color(&theme.help_desc)?;
+        write!(out, " {}", desc)?;
+        col += key_width;
+    }
+    Ok(())
+}
+
+/// A caller-provided line rendered beneath the help keys, recomputed each frame
+pub(crate) type FooterFn<'a> = Box<dyn Fn() -> String + 'a>;
+
+/// Write the footer line, if any
+pub(crate) fn render_footer(
+    out: &mut Buffer,
+    theme: &Theme,
+    footer: &Option<FooterFn>,
+) -> io::Result<()> {
+    if let Some(footer) = footer {
+        out.set_color(&theme.description)?;
+        writeln!(out, "{}", footer())?;
+        out.reset()?;
+    }
+    Ok(())
+}
+
+/// Render a full list of keybindings which is dismissed by pressing any key
+pub(crate) fn render_overlay(
+    theme: &Theme,
+    title: &str,
+    keys: &[(&str, &str)],
+) -> io::Result<String> {
+    let mut out = Buffer::ansi();
+
+    out.set_color(&theme.title)?;
+    writeln!(out, "{}", title)?;
+
+    let keys = keys
+        .iter()
+        .map(|(key, desc)| (theme::help_key(key), *desc))
+        .collect::<Vec<_>>();
+    let key_width = keys
+        .iter()
+        .map(|(key, _)| console::measure_text_width(key))
+        .max()
+        .unwrap_or(0);
+    for (key, desc) in &keys {
+        out.set_color(&theme.help_key)?;
+        write!(
+            out,
+            "  {}",
+            console::pad_str(key, key_width, console::Alignment::Left, None)
+        )?;
+        out.set_color(&theme.help_desc)?;
+        writeln!(out, "  {}", desc)?;
+    }
+
+    out.set_color(&theme.description)?;
+    writeln!(out, "press any key to close")?;
+    out.reset()?;
+
+    Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::without_ansi;
+
+    #[test]
+    fn test_render_keys_wraps() {
+        let mut out = Buffer::ansi();
+        let keys = [("↑/↓", "up/down"), ("x", "toggle"), ("enter", "confirm")];
+        render_keys(&mut out, &Theme::default(), &keys, false, 0, 22).unwrap();
+
+        assert_eq!(
+            "↑/↓ up/down • x toggle\nenter confirm",
+            without_ansi(std::str::from_utf8(out.as_slice()).unwrap())
+        );
+    }
+}
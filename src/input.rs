@@ -1,13 +1,77 @@
 use std::{
+    borrow::Cow,
     char,
     io::{self, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
 };
 
 use console::{measure_text_width, Key, Term};
 use termcolor::{Buffer, WriteColor};
 
+use crate::cancel::{self, CancelToken};
 use crate::ctrlc;
-use crate::{theme, Theme};
+use crate::help::{self, FooterFn};
+use crate::outcome::{self, Outcome, SuccessFormatFn};
+use crate::remember::{self, Remembered};
+use crate::select::CTRL_U;
+use crate::{event, term, theme, PromptEvent, PromptMetrics, PromptResult, Theme};
+
+/// Checks the text entered in an `Input` before it is submitted
+///
+/// Implemented for closures returning an error message, and by the ready-made validators in
+/// [`validators`](crate::validators).
+pub trait InputValidator {
+    /// Returns the message to display if `value` is not valid
+    fn validate(&self, value: &str) -> Result<(), String>;
+}
+
+impl<F: Fn(&str) -> Result<(), String>> InputValidator for F {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        self(value)
+    }
+}
+
+fn is_word_separator(c: char) -> bool {
+    c.is_ascii_punctuation() || c.is_ascii_whitespace()
+}
+
+/// Fill the slots of `pattern` with `input`, stopping after the last filled slot
+fn apply_mask(pattern: &str, input: &str) -> String {
+    let mut chars = input.chars();
+    let mut out = String::new();
+    let mut end = 0;
+    for c in pattern.chars() {
+        if c != MASK_SLOT {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(c) => {
+                out.push(c);
+                end = out.len();
+            }
+            None => {
+                out.truncate(end);
+                return out;
+            }
+        }
+    }
+    out
+}
+
+/// The column of slot `slot` in `pattern`, or the pattern's width if there is no such slot
+fn mask_column(pattern: &str, slot: usize) -> usize {
+    pattern
+        .char_indices()
+        .filter(|&(_, c)| c == MASK_SLOT)
+        .nth(slot)
+        .map(|(i, _)| measure_text_width(&pattern[..i]))
+        .unwrap_or_else(|| measure_text_width(pattern))
+}
+
+type InputChangeFn<'a> = Box<dyn Fn(&str) + 'a>;
 
 /// Single line text input
 ///
@@ -45,10 +109,18 @@ pub struct Input<'a> {
     pub inline: bool,
     /// Whether to mask the input
     pub password: bool,
+    /// Whether to visualize tabs and trailing spaces
+    pub show_whitespace: bool,
+    /// Number of columns a tab is displayed as
+    pub tab_width: usize,
+    /// Whether to display the number of characters entered after the input
+    pub show_counter: bool,
+    /// A soft limit the counter is displayed against
+    pub counter_max: Option<usize>,
     /// Input entered by the user
     pub input: String,
     /// Colors/style of the input
-    pub theme: &'a Theme,
+    pub theme: Cow<'a, Theme>,
     /// Validation function
     pub validation: fn(&str) -> Result<(), &str>,
 
@@ -56,14 +128,36 @@ pub struct Input<'a> {
     cursor: usize,
     height: usize,
     term: Term,
+    cancel_token: Option<CancelToken>,
     err: Option<String>,
     suggestion: Option<String>,
+    on_input_change: Option<InputChangeFn<'a>>,
+    validators: Vec<Box<dyn InputValidator + 'a>>,
+    observer: Option<Sender<PromptEvent>>,
+    success_format: Option<SuccessFormatFn<'a>>,
+    tee: Option<Box<dyn Write + 'a>>,
+    footer: Option<FooterFn<'a>>,
+    accept_suggestion_key: Key,
+    suggestion_idx: usize,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    metrics: PromptMetrics,
+    remember: Option<PathBuf>,
+    fixed_position: bool,
+    // the row and column the input starts at in the last render
+    input_pos: (usize, usize),
+    mask: Option<String>,
+    mask_raw_value: bool,
+    max_length: Option<usize>,
+    numeric: bool,
+    decimal: bool,
+    show_cursor_block: bool,
 }
 
-const CTRL_U: char = '\u{15}';
 const CTRL_W: char = '\u{17}';
 
-const ERR_MSG_HEIGHT: usize = 2;
+/// Marks a character typed by the user in a `mask_pattern`
+const MASK_SLOT: char = '_';
 
 impl<'a> Input<'a> {
     /// Creates a new input with the given title.
@@ -77,15 +171,40 @@ impl<'a> Input<'a> {
             input: String::new(),
             inline: false,
             password: false,
-            theme: &*theme::DEFAULT,
+            show_counter: false,
+            counter_max: None,
+            show_whitespace: false,
+            tab_width: 4,
+            theme: Cow::Owned(theme::default_theme()),
             validation: |_| Ok(()),
 
             // Internal state
             cursor: 0,
             height: 0,
-            term: Term::stderr(),
+            term: term::default_term(),
+            cancel_token: None,
             err: None,
             suggestion: None,
+            on_input_change: None,
+            validators: vec![],
+            observer: None,
+            success_format: None,
+            tee: None,
+            footer: None,
+            accept_suggestion_key: Key::Tab,
+            suggestion_idx: 0,
+            timeout: None,
+            deadline: None,
+            metrics: PromptMetrics::default(),
+            remember: None,
+            fixed_position: false,
+            input_pos: (0, 0),
+            mask: None,
+            mask_raw_value: false,
+            max_length: None,
+            numeric: false,
+            decimal: false,
+            show_cursor_block: true,
         }
     }
 
@@ -113,6 +232,71 @@ impl<'a> Input<'a> {
         self
     }
 
+    /// Sets whether whitespace is visualized.
+    ///
+    /// If true, tabs are displayed as `→` and trailing spaces as `·`. The returned value keeps the
+    /// real characters.
+    pub fn show_whitespace(mut self, show_whitespace: bool) -> Self {
+        self.show_whitespace = show_whitespace;
+        self
+    }
+
+    /// Sets the number of columns a tab is displayed as with `show_whitespace`. Defaults to 4.
+    ///
+    /// Without `show_whitespace`, tabs are written as they are.
+    pub fn tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    /// Sets whether the number of characters entered is displayed after the input
+    pub fn show_counter(mut self, show_counter: bool) -> Self {
+        self.show_counter = show_counter;
+        self
+    }
+
+    /// Sets a soft limit for the counter, displayed as `12/50`.
+    ///
+    /// Longer values are still accepted; the counter is colored as an error once the value
+    /// reaches 90% of the limit. Implies `show_counter`.
+    pub fn counter_max(mut self, counter_max: usize) -> Self {
+        self.show_counter = true;
+        self.counter_max = Some(counter_max);
+        self
+    }
+
+    /// Sets the maximum number of characters that can be typed
+    ///
+    /// Keys are ignored once the limit is reached. With `show_counter` and no `counter_max`, the
+    /// counter is displayed against this limit.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Sets whether only digits can be typed
+    pub fn numeric(mut self, numeric: bool) -> Self {
+        self.numeric = numeric;
+        self
+    }
+
+    /// Sets whether only a decimal number such as `-12.5` can be typed
+    ///
+    /// Digits, a single `.` and a leading `-` are accepted. Enabling it implies `numeric`.
+    pub fn decimal(mut self, decimal: bool) -> Self {
+        self.numeric |= decimal;
+        self.decimal = decimal;
+        self
+    }
+
+    /// Sets whether a block is drawn as the cursor after the end of the input. Defaults to true.
+    ///
+    /// When false, the terminal's own cursor is left visible instead.
+    pub fn show_cursor_block(mut self, show_cursor_block: bool) -> Self {
+        self.show_cursor_block = show_cursor_block;
+        self
+    }
+
     /// Sets the placeholder of the input.
     ///
     /// The placeholder is displayed in the input before the user enters any text
@@ -127,6 +311,12 @@ impl<'a> Input<'a> {
         self
     }
 
+    /// Sets the key that accepts the current suggestion. Defaults to `Key::Tab`.
+    pub fn accept_suggestion_key(mut self, key: Key) -> Self {
+        self.accept_suggestion_key = key;
+        self
+    }
+
     /// Sets the prompt of the input.
     ///
     /// The prompt is displayed after the title and description. If empty, the default prompt `> ` is displayed.
@@ -135,9 +325,84 @@ impl<'a> Input<'a> {
         self
     }
 
+    /// Sets whether the input is drawn at a fixed position, so running it repeatedly does not
+    /// move down the screen
+    ///
+    /// The cursor position is saved when the input starts, each redraw clears from there to the
+    /// end of the screen, and the cursor is returned there once it finishes. This is meant for
+    /// live dashboards; if drawing scrolls the terminal, the saved position no longer lines up.
+    pub fn fixed_position(mut self, fixed_position: bool) -> Self {
+        self.fixed_position = fixed_position;
+        self
+    }
+
+    /// Sets a pattern such as `(___) ___-____` the input is typed into
+    ///
+    /// Each `_` is a slot filled by a typed character; every other character is inserted
+    /// automatically and skipped over by the cursor. Typing one of those fixed characters is
+    /// ignored, as is typing once every slot is filled. `run` returns the formatted text up to
+    /// the last filled slot.
+    pub fn mask_pattern(mut self, pattern: &str) -> Self {
+        self.mask = Some(pattern.to_string());
+        self
+    }
+
+    /// Sets whether a masked input returns only the typed characters, e.g. `5551234567`, instead
+    /// of the formatted text
+    pub fn mask_raw_value(mut self, raw: bool) -> Self {
+        self.mask_raw_value = raw;
+        self
+    }
+
+    /// Sets a static line to display beneath the input
+    pub fn footer(mut self, footer: &str) -> Self {
+        let footer = footer.to_string();
+        self.footer = Some(Box::new(move || footer.clone()));
+        self
+    }
+
+    /// Sets a status line to display beneath the input, recomputed each time it is drawn
+    pub fn status<F: Fn() -> String + 'a>(mut self, status: F) -> Self {
+        self.footer = Some(Box::new(status));
+        self
+    }
+
+    /// Remember the submitted value in the JSON file at `path`, keyed by title, and pre-fill it
+    /// the next time the input runs
+    ///
+    /// Password inputs are never remembered. Failures to read or write the file are ignored.
+    pub fn remember(mut self, path: &Path) -> Self {
+        self.remember = Some(path.to_path_buf());
+        self
+    }
+
+    /// Sets the terminal the input reads keys from and is drawn on, e.g. a pseudo-terminal in
+    /// tests. Defaults to the terminal set with `set_term`.
+    pub fn term(mut self, term: Term) -> Self {
+        self.term = term;
+        self
+    }
+
+    /// Cancel the input with an `Interrupted` error once `token` is cancelled, e.g. from another
+    /// thread
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
     /// Sets the theme of the input
-    pub fn theme(mut self, theme: &'a Theme) -> Self {
-        self.theme = theme;
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Sets how long to wait for the user to submit.
+    ///
+    /// A countdown is shown below the input. When it runs out, whatever has been typed so far is
+    /// returned without validation. On Windows, where key reads can't time out, the countdown only
+    /// updates and expires when a key is pressed.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
         self
     }
 
@@ -149,14 +414,98 @@ impl<'a> Input<'a> {
         self
     }
 
+    /// Sets the validator for the input, checked after `validation` and replacing any added with
+    /// `add_validator`
+    ///
+    /// # Example
+    /// ```rust
+    /// use demand::{validators, Input};
+    ///
+    /// let input = Input::new("Port").validator(validators::IntRange(1, 65535));
+    /// ```
+    pub fn validator(mut self, validator: impl InputValidator + 'a) -> Self {
+        self.validators = vec![Box::new(validator)];
+        self
+    }
+
+    /// Adds a validator checked after the ones before it, so the first failure is displayed
+    pub fn add_validator(mut self, validator: impl InputValidator + 'a) -> Self {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Sets a callback that is invoked with the current input after each edit.
+    ///
+    /// The callback only observes the value; it cannot change it.
+    pub fn on_input_change<F: Fn(&str) + 'a>(mut self, on_input_change: F) -> Self {
+        self.on_input_change = Some(Box::new(on_input_change));
+        self
+    }
+
+    /// Report the outcome of the input to `observer` in addition to returning it
+    ///
+    /// Password input is reported masked.
+    pub fn observe(mut self, observer: Sender<PromptEvent>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Checks `value` against the configured validation without displaying the input
+    pub fn validate_value(&self, value: &str) -> Result<(), String> {
+        (self.validation)(value).map_err(|err| err.to_string())?;
+        self.validators
+            .iter()
+            .try_for_each(|validator| validator.validate(value))
+    }
+
+    /// Set how the line shown after submitting is formatted, replacing the default "Title value"
+    ///
+    /// Return an empty string to show nothing.
+    pub fn success_format(mut self, format: impl Fn(&Outcome) -> String + 'a) -> Self {
+        self.success_format = Some(Box::new(format));
+        self
+    }
+
+    /// Also write the line shown after submitting to `sink`, without colors
+    ///
+    /// Errors writing to `sink` are ignored.
+    pub fn tee(mut self, sink: Box<dyn Write + 'a>) -> Self {
+        self.tee = Some(sink);
+        self
+    }
+
+    /// Displays the input like `run`, returning whether it was submitted, cancelled or failed
+    pub fn run_result(self) -> PromptResult<String> {
+        self.run().into()
+    }
+
     /// Displays the input to the user and returns the response
     ///
     /// This function will block until the user submits the input. If the user cancels the input,
     /// an error of type `io::ErrorKind::Interrupted` is returned.
-    pub fn run(mut self) -> io::Result<String> {
+    pub fn run(self) -> io::Result<String> {
+        self.run_with_metrics().map(|(input, _)| input)
+    }
+
+    /// Displays the input like `run`, also returning how the user interacted with it
+    ///
+    /// Moving the cursor within the text counts as navigation.
+    pub fn run_with_metrics(mut self) -> io::Result<(String, PromptMetrics)> {
+        let started = Instant::now();
         let ctrlc_handle = ctrlc::show_cursor_after_ctrlc(&self.term)?;
+        if self.fixed_position {
+            term::save_cursor(&self.term)?;
+        }
 
-        self.term.hide_cursor()?;
+        if let Some(Remembered::One(input)) = self.remembered() {
+            self.cursor = input.chars().count();
+            self.input = input;
+        }
+
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        if self.show_cursor_block {
+            self.term.hide_cursor()?;
+        }
         loop {
             self.clear()?;
             let output = self.render()?;
@@ -164,10 +513,25 @@ impl<'a> Input<'a> {
             self.height = output.lines().count() - 1;
             self.term.write_all(output.as_bytes())?;
             self.term.flush()?;
-            self.set_cursor()?;
-
-            let key = self.term.read_key()?;
+            let lift = self.set_cursor(&output)?;
+            let key = self.read_key();
+            self.term.move_cursor_down(lift)?;
+
+            let key = match key? {
+                Some(key) => key,
+                None if self.remaining().is_some_and(|r| r.is_zero()) => {
+                    self.clear_err();
+                    self.term.clear_to_end_of_screen()?;
+                    self.term.show_cursor()?;
+                    ctrlc_handle.close();
+                    return self.handle_submit(started);
+                }
+                None => continue,
+            };
+            self.metrics.keystrokes += 1;
+            let prev_input = self.on_input_change.as_ref().map(|_| self.input.clone());
             match key {
+                _ if key == self.accept_suggestion_key => self.handle_tab()?,
                 Key::Char(CTRL_U) => self.handle_ctrl_u()?,
                 Key::Char(CTRL_W) => self.handle_ctrl_w()?,
                 Key::Char(c) => self.handle_key(c)?,
@@ -176,38 +540,101 @@ impl<'a> Input<'a> {
                 Key::ArrowRight => self.handle_arrow_right()?,
                 Key::Home => self.handle_home()?,
                 Key::End => self.handle_end()?,
+                Key::BackTab => self.handle_back_tab(),
+                Key::UnknownEscSeq(ref seq) => self.handle_esc_seq(seq)?,
                 Key::Enter => {
-                    self.clear_err()?;
+                    self.clear_err();
                     self.validate()?;
                     if self.err.is_none() {
                         self.term.clear_to_end_of_screen()?;
                         self.term.show_cursor()?;
                         ctrlc_handle.close();
-                        return self.handle_submit();
+                        return self.handle_submit(started);
                     }
                 }
-                Key::Tab => self.handle_tab()?,
                 Key::Escape => {
+                    if self.fixed_position {
+                        self.clear()?;
+                    }
                     self.term.show_cursor()?;
                     ctrlc_handle.close();
+                    event::cancelled(&self.observer, &self.title);
                     return Err(io::Error::new(io::ErrorKind::Interrupted, "user cancelled"));
                 }
                 _ => {}
             }
             if key != Key::Enter {
-                self.clear_err()?;
+                self.clear_err();
+            }
+            if key != Key::BackTab {
+                self.suggestion_idx = 0;
             }
             self.suggest()?;
+            self.notify_input_change(prev_input);
+        }
+    }
+
+    /// Calls the `on_input_change` hook if the input differs from `prev_input`
+    fn notify_input_change(&self, prev_input: Option<String>) {
+        if let (Some(on_input_change), Some(prev_input)) = (&self.on_input_change, prev_input) {
+            if prev_input != self.input {
+                on_input_change(&self.input);
+            }
         }
     }
 
+    /// Reads the next key, or returns `None` once a second while a timeout counts down
+    fn read_key(&self) -> io::Result<Option<Key>> {
+        match self.remaining() {
+            Some(remaining) => {
+                let timeout = remaining.min(Duration::from_secs(1));
+                cancel::read_key_timeout(&self.term, self.cancel_token.as_ref(), timeout)
+            }
+            None => cancel::read_key(&self.term, self.cancel_token.as_ref()).map(Some),
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
     fn handle_key(&mut self, c: char) -> io::Result<()> {
+        if let Some(mask) = &self.mask {
+            let slots = mask.chars().filter(|&m| m == MASK_SLOT).count();
+            if self.input.chars().count() >= slots || (c != MASK_SLOT && mask.contains(c)) {
+                return Ok(());
+            }
+        }
+        if self.is_full() || !self.accepts_key(c) {
+            return Ok(());
+        }
         let idx = self.get_char_idx(&self.input, self.cursor);
         self.input.insert(idx, c);
         self.cursor += 1;
         Ok(())
     }
 
+    /// Whether `c` can be typed at the cursor in numeric mode
+    fn accepts_key(&self, c: char) -> bool {
+        if !self.numeric {
+            return true;
+        }
+        let after_sign = self.cursor > 0 || !self.input.starts_with('-');
+        match c {
+            '0'..='9' => after_sign,
+            '-' => self.decimal && self.cursor == 0 && after_sign,
+            '.' => self.decimal && after_sign && !self.input.contains('.'),
+            _ => false,
+        }
+    }
+
+    /// Whether `max_length` characters have been typed
+    fn is_full(&self) -> bool {
+        self.max_length
+            .is_some_and(|max| self.input.chars().count() >= max)
+    }
+
     fn handle_ctrl_u(&mut self) -> io::Result<()> {
         let idx = self.get_char_idx(&self.input, self.cursor);
         self.input.replace_range(..idx, "");
@@ -224,9 +651,9 @@ impl<'a> Input<'a> {
         let idx = self.get_char_idx(&self.input, self.cursor);
         let slice = &self.input[0..idx];
         let offset = slice
-            .trim_end_matches(|c: char| c.is_ascii_punctuation() || c.is_ascii_whitespace())
+            .trim_end_matches(is_word_separator)
             .char_indices()
-            .rfind(|&(_, x)| x.is_ascii_punctuation() || x.is_ascii_whitespace())
+            .rfind(|&(_, x)| is_word_separator(x))
             .map(|(i, _)| i)
             .unwrap_or(0);
         let from = match offset > 0 {
@@ -244,6 +671,61 @@ impl<'a> Input<'a> {
         Ok(())
     }
 
+    /// Handles word movement, which console does not parse: Ctrl/Alt+Left/Right arrive as
+    /// `\x1b[1;` followed by the modifier and direction, and Alt+B/F as `\x1bb`/`\x1bf`
+    fn handle_esc_seq(&mut self, seq: &[char]) -> io::Result<()> {
+        let direction = match seq {
+            ['[', '1', ';'] => match (self.term.read_key()?, self.term.read_key()?) {
+                (Key::Char('3' | '5'), Key::Char(c)) => c,
+                _ => return Ok(()),
+            },
+            ['b'] => 'D',
+            ['f'] => 'C',
+            _ => return Ok(()),
+        };
+        match direction {
+            'D' => self.handle_word_left(),
+            'C' => self.handle_word_right(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_word_left(&mut self) {
+        self.metrics.navigations += 1;
+        // is masked, jump to the start to not reveal whitespace
+        if self.password {
+            self.cursor = 0;
+            return;
+        }
+        let chars = self.input.chars().collect::<Vec<_>>();
+        let mut cursor = self.cursor.min(chars.len());
+        while cursor > 0 && is_word_separator(chars[cursor - 1]) {
+            cursor -= 1;
+        }
+        while cursor > 0 && !is_word_separator(chars[cursor - 1]) {
+            cursor -= 1;
+        }
+        self.cursor = cursor;
+    }
+
+    fn handle_word_right(&mut self) {
+        self.metrics.navigations += 1;
+        let chars = self.input.chars().collect::<Vec<_>>();
+        if self.password {
+            self.cursor = chars.len();
+            return;
+        }
+        let mut cursor = self.cursor.min(chars.len());
+        while cursor < chars.len() && is_word_separator(chars[cursor]) {
+            cursor += 1;
+        }
+        while cursor < chars.len() && !is_word_separator(chars[cursor]) {
+            cursor += 1;
+        }
+        self.cursor = cursor;
+    }
+
     fn handle_backspace(&mut self) -> io::Result<()> {
         let chars_count = self.input.chars().count();
         if chars_count > 0 && self.cursor > 0 {
@@ -257,6 +739,7 @@ impl<'a> Input<'a> {
     }
 
     fn handle_arrow_left(&mut self) -> io::Result<()> {
+        self.metrics.navigations += 1;
         if self.cursor > 0 {
             self.cursor -= 1;
         }
@@ -264,6 +747,7 @@ impl<'a> Input<'a> {
     }
 
     fn handle_arrow_right(&mut self) -> io::Result<()> {
+        self.metrics.navigations += 1;
         if self.cursor < self.input.chars().count() {
             self.cursor += 1;
         }
@@ -271,28 +755,63 @@ impl<'a> Input<'a> {
     }
 
     fn handle_home(&mut self) -> io::Result<()> {
+        self.metrics.navigations += 1;
         self.cursor = 0;
         Ok(())
     }
 
     fn handle_end(&mut self) -> io::Result<()> {
+        self.metrics.navigations += 1;
         self.cursor = self.input.chars().count();
         Ok(())
     }
 
     fn handle_tab(&mut self) -> io::Result<()> {
-        if self.suggestion.is_some() {
-            self.input.push_str(self.suggestion.as_ref().unwrap());
+        if let Some(suggestion) = self.suggestion.clone() {
             self.cursor = self.input.chars().count();
+            // the suggestion goes through the same checks as typed keys
+            for c in suggestion.chars() {
+                if self.is_full() || !self.accepts_key(c) {
+                    break;
+                }
+                self.input.push(c);
+                self.cursor += 1;
+            }
         }
         Ok(())
     }
 
-    fn handle_submit(mut self) -> io::Result<String> {
+    /// Cycles the suggestion backward to the previous match, wrapping around
+    fn handle_back_tab(&mut self) {
+        let count = self.matching_suggestions().len();
+        if count > 0 {
+            self.suggestion_idx = (self.suggestion_idx + count - 1) % count;
+        }
+    }
+
+    fn remembered(&self) -> Option<Remembered> {
+        let path = self.remember.as_ref().filter(|_| !self.password)?;
+        remember::load(path, &self.title)
+    }
+
+    fn handle_submit(mut self, started: Instant) -> io::Result<(String, PromptMetrics)> {
         self.clear()?;
         let output = self.render_success()?;
         self.term.write_all(output.as_bytes())?;
-        Ok(self.input)
+        term::record_height(&self.term, &output);
+        term::tee(&mut self.tee, &output);
+        if self.fixed_position {
+            term::restore_cursor(&self.term)?;
+        }
+        event::submitted(&self.observer, &self.title, &self.display_value());
+        if let Some(path) = self.remember.as_ref().filter(|_| !self.password) {
+            remember::save(path, &self.title, Remembered::One(self.input.clone()));
+        }
+        let metrics = PromptMetrics {
+            elapsed: started.elapsed(),
+            ..self.metrics
+        };
+        Ok((self.value(), metrics))
     }
 
     fn render(&mut self) -> io::Result<String> {
@@ -321,29 +840,40 @@ impl<'a> Input<'a> {
         }
         out.reset()?;
 
+        let text = String::from_utf8_lossy(out.as_slice());
+        let line = text.rsplit('\n').next().unwrap_or_default();
+        self.input_pos = (term::row(&out), console::measure_text_width(line));
         self.render_input(&mut out)?;
+        self.render_counter(&mut out)?;
 
-        if self.err.is_some() {
+        if let Some(err) = &self.err {
             out.set_color(&self.theme.error_indicator)?;
             writeln!(out)?;
             writeln!(out)?;
-            write!(out, "* {}", self.err.as_ref().unwrap())?;
+            write!(out, "* {}", err)?;
             out.reset()?;
         }
 
         writeln!(out)?;
         out.reset()?;
+        if let Some(remaining) = self.remaining() {
+            out.set_color(&self.theme.description)?;
+            writeln!(out, "{}s remaining", remaining.as_secs_f64().ceil())?;
+            out.reset()?;
+        }
+        help::render_footer(&mut out, &self.theme, &self.footer)?;
 
         Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
     }
 
     fn render_input(&mut self, out: &mut Buffer) -> io::Result<String> {
-        let input = match self.password {
-            true => self.input.chars().map(|_| '*').collect::<String>(),
-            false => self.input.to_string(),
-        };
+        let input = self.masked_input();
 
-        if !self.placeholder.is_empty() && self.input.is_empty() {
+        if let Some(mask) = &self.mask {
+            self.render_mask(out, mask, &input)?;
+            return Ok(input);
+        }
+        if self.showing_placeholder() {
             out.set_color(
                 &self
                     .theme
@@ -358,22 +888,27 @@ impl<'a> Input<'a> {
             return Ok(input);
         }
 
-        let cursor_idx = self.get_char_idx(&input, self.cursor);
-        write!(out, "{}", &input[..cursor_idx])?;
+        let segments = self.display_segments(&input);
+        let at_end = self.cursor >= segments.len();
+        write!(
+            out,
+            "{}",
+            segments[..self.cursor.min(segments.len())].concat()
+        )?;
 
-        if cursor_idx < input.len() {
+        if let Some(segment) = segments.get(self.cursor) {
             out.set_color(&self.theme.real_cursor_color(None))?;
-            write!(out, "{}", &input[cursor_idx..cursor_idx + 1])?;
+            write!(out, "{segment}")?;
             out.reset()?;
         }
-        if cursor_idx + 1 < input.len() {
+        if self.cursor + 1 < segments.len() {
             out.reset()?;
-            write!(out, "{}", &input[cursor_idx + 1..])?;
+            write!(out, "{}", segments[self.cursor + 1..].concat())?;
         }
 
         if let Some(suggestion) = &self.suggestion {
             if !suggestion.is_empty() {
-                if cursor_idx >= input.len() {
+                if at_end {
                     out.set_color(
                         &self
                             .theme
@@ -389,46 +924,164 @@ impl<'a> Input<'a> {
                     write!(out, "{suggestion}")?;
                 }
                 out.reset()?;
-            } else if cursor_idx >= input.len() {
-                out.set_color(&self.theme.real_cursor_color(None))?;
-                write!(out, " ")?;
-                out.reset()?;
+            } else if at_end {
+                self.render_cursor_block(out)?;
             }
-        } else if cursor_idx >= input.len() {
+        } else if at_end {
+            self.render_cursor_block(out)?;
+        }
+
+        Ok(input)
+    }
+
+    fn render_cursor_block(&self, out: &mut Buffer) -> io::Result<()> {
+        if !self.show_cursor_block {
+            return Ok(());
+        }
+        out.set_color(&self.theme.real_cursor_color(None))?;
+        write!(out, " ")?;
+        out.reset()
+    }
+
+    /// Draws `pattern` with the typed characters in its slots and the cursor on the next slot
+    fn render_mask(&self, out: &mut Buffer, pattern: &str, input: &str) -> io::Result<()> {
+        let mut chars = input.chars();
+        let mut slot = 0;
+        for c in pattern.chars() {
+            if c != MASK_SLOT {
+                write!(out, "{c}")?;
+                continue;
+            }
+            let (c, color) = match chars.next() {
+                Some(c) => (c, None),
+                None => (MASK_SLOT, Some(&self.theme.input_placeholder)),
+            };
+            if slot == self.cursor {
+                out.set_color(&self.theme.real_cursor_color(color))?;
+            } else if let Some(color) = color {
+                out.set_color(color)?;
+            }
+            write!(out, "{c}")?;
+            out.reset()?;
+            slot += 1;
+        }
+        if self.cursor >= slot {
             out.set_color(&self.theme.real_cursor_color(None))?;
             write!(out, " ")?;
             out.reset()?;
         }
+        Ok(())
+    }
 
-        Ok(input)
+    fn render_counter(&self, out: &mut Buffer) -> io::Result<()> {
+        if !self.show_counter {
+            return Ok(());
+        }
+        let count = self.input.chars().count();
+        match self.counter_max.or(self.max_length) {
+            Some(max) => {
+                if count * 10 >= max * 9 {
+                    out.set_color(&self.theme.error_indicator)?;
+                } else {
+                    out.set_color(&self.theme.description)?;
+                }
+                write!(out, " {count}/{max}")?;
+            }
+            None => {
+                out.set_color(&self.theme.description)?;
+                write!(out, " {count}")?;
+            }
+        }
+        out.reset()
+    }
+
+    fn masked_input(&self) -> String {
+        match self.password {
+            true => self.input.chars().map(|_| '*').collect::<String>(),
+            false => self.input.to_string(),
+        }
+    }
+
+    /// The text displayed for each character of `input`, accounting for visualized whitespace
+    fn display_segments(&self, input: &str) -> Vec<String> {
+        let trailing_from = input.trim_end_matches(' ').chars().count();
+        input
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                '\t' if self.show_whitespace => {
+                    format!(
+                        "{}{}",
+                        theme::glyph("→", ">"),
+                        " ".repeat(self.tab_width.saturating_sub(1))
+                    )
+                }
+                ' ' if self.show_whitespace && i >= trailing_from => {
+                    theme::glyph("·", ".").to_string()
+                }
+                c => c.to_string(),
+            })
+            .collect()
+    }
+
+    /// The display width of the first `cursor` characters of the input
+    fn display_width(&self, cursor: usize) -> usize {
+        if let Some(mask) = &self.mask {
+            return mask_column(mask, cursor);
+        }
+        self.display_segments(&self.masked_input())
+            .iter()
+            .take(cursor)
+            .map(|s| s.chars().count())
+            .sum()
     }
 
     fn render_success(&mut self) -> io::Result<String> {
+        if let Some(format) = &self.success_format {
+            let value = self.display_value();
+            let outcome = Outcome {
+                title: &self.title,
+                values: vec![&value],
+            };
+            return Ok(outcome::render(format, &outcome));
+        }
         let mut out = Buffer::ansi();
         out.set_color(&self.theme.title)?;
         write!(out, "{}", self.title)?;
         out.set_color(&self.theme.selected_option)?;
-        writeln!(
-            out,
-            " {}",
-            match self.password {
-                true => (1..13).map(|_| '*').collect::<String>(),
-                false => self.input.to_string(),
-            }
-        )?;
+        writeln!(out, " {}", self.display_value())?;
         out.reset()?;
         Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
     }
 
+    fn display_value(&self) -> String {
+        match self.password {
+            true => (1..13).map(|_| '*').collect::<String>(),
+            false => self.value(),
+        }
+    }
+
+    /// The value `run` returns: the input, formatted by the mask pattern if there is one
+    fn value(&self) -> String {
+        match &self.mask {
+            Some(mask) if !self.mask_raw_value => apply_mask(mask, &self.input),
+            _ => self.input.clone(),
+        }
+    }
+
+    fn showing_placeholder(&self) -> bool {
+        !self.placeholder.is_empty() && self.input.is_empty() && self.mask.is_none()
+    }
+
     fn suggest(&mut self) -> io::Result<()> {
         if self.input.is_empty() {
             self.suggestion = None;
             return Ok(());
         }
-        if let Some(suggestions) = &self.suggestions {
-            self.suggestion = suggestions
-                .iter()
-                .find(|s| s.to_lowercase().starts_with(&self.input.to_lowercase()))
+        if self.suggestions.is_some() {
+            self.suggestion = self
+                .matching_suggestions()
+                .get(self.suggestion_idx)
                 .and_then(|s| {
                     let suggestion = s[self.input.len()..].to_string();
                     (!suggestion.is_empty()).then_some(suggestion)
@@ -437,10 +1090,18 @@ impl<'a> Input<'a> {
         Ok(())
     }
 
+    fn matching_suggestions(&self) -> Vec<&'a str> {
+        let input = self.input.to_lowercase();
+        self.suggestions
+            .unwrap_or_default()
+            .iter()
+            .filter(|s| s.to_lowercase().starts_with(&input))
+            .copied()
+            .collect()
+    }
+
     fn validate(&mut self) -> io::Result<()> {
-        self.err = (self.validation)(&self.input)
-            .map_err(|err| err.to_string())
-            .err();
+        self.err = self.validate_value(&self.value()).err();
         Ok(())
     }
 
@@ -452,53 +1113,27 @@ impl<'a> Input<'a> {
             .unwrap_or(input.len())
     }
 
-    fn set_cursor(&mut self) -> io::Result<()> {
-        // if we have a placeholder, move the cursor left to beginning of the input
-        if !self.placeholder.is_empty() && self.input.is_empty() {
-            self.term
-                .move_cursor_left(self.placeholder.chars().count())?;
-        } else {
-            self.term.move_cursor_left(self.input.chars().count())?;
-        }
-
-        // if we have a suggestion, move the cursor left to end of the input
-        if self.suggestion.is_some() {
-            self.term
-                .move_cursor_left(self.suggestion.as_ref().unwrap().chars().count())?;
-        }
-
-        // if there is an error, move the cursor up from error message and right to the input
-        match self.err {
-            Some(_) => {
-                let err_count = self.err.as_ref().unwrap().chars().count();
-                self.term.move_cursor_left(err_count + 2)?; // 2 for the error prefix
-                self.term.move_cursor_up(ERR_MSG_HEIGHT)?;
-                let mut offset = 0;
-                if self.inline {
-                    offset += self.title.chars().count();
-                    offset += self.description.chars().count();
-                    offset += self.prompt.chars().count();
-                } else {
-                    offset += self.prompt.chars().count();
-                }
-                offset += self.cursor;
-                self.term.move_cursor_right(offset)?;
-            }
-            None => self.term.move_cursor_right(self.cursor)?,
-        }
-        Ok(())
+    /// Moves the cursor from just below `output` to its place in the input, returning how many
+    /// lines it moved up
+    fn set_cursor(&self, output: &str) -> io::Result<usize> {
+        let (row, column) = self.input_pos;
+        let lift = term::lift_cursor(&self.term, output, row)?;
+        self.term
+            .move_cursor_right(column + self.display_width(self.cursor))?;
+        Ok(lift)
     }
 
-    fn clear_err(&mut self) -> io::Result<()> {
-        if self.err.is_some() {
-            self.err = None;
-            self.term.move_cursor_down(ERR_MSG_HEIGHT)?;
-        }
-        Ok(())
+    fn clear_err(&mut self) {
+        self.err = None;
     }
 
     fn clear(&mut self) -> io::Result<()> {
-        self.term.clear_last_lines(self.height)?;
+        if self.fixed_position {
+            term::restore_cursor(&self.term)?;
+            self.term.clear_to_end_of_screen()?;
+        } else {
+            self.term.clear_last_lines(self.height)?;
+        }
         self.height = 0;
         Ok(())
     }
@@ -626,4 +1261,285 @@ mod tests {
             without_ansi(input.render().unwrap().as_str())
         );
     }
+
+    #[test]
+    fn test_back_tab_cycles_suggestions() {
+        let mut input = Input::new("Title").suggestions(&["apple", "apricot", "avocado"]);
+        input.input = "ap".to_string();
+        input.suggest().unwrap();
+        assert_eq!(Some("ple".to_string()), input.suggestion);
+
+        input.handle_back_tab();
+        input.suggest().unwrap();
+        assert_eq!(Some("ricot".to_string()), input.suggestion);
+
+        input.handle_back_tab();
+        input.suggest().unwrap();
+        assert_eq!(Some("ple".to_string()), input.suggestion);
+    }
+
+    #[test]
+    fn test_render_timeout() {
+        let mut input = Input::new("Title").timeout(Duration::from_secs(10));
+        input.deadline = Some(Instant::now() + Duration::from_secs(10));
+
+        assert_eq!(
+            "Title\n>  \n10s remaining\n",
+            without_ansi(input.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn test_validate_value() {
+        let input = Input::new("Title").validation(NON_EMPTY);
+
+        assert_eq!(Ok(()), input.validate_value("name"));
+        assert_eq!(
+            Err("Name cannot be empty".to_string()),
+            input.validate_value("")
+        );
+    }
+
+    #[test]
+    fn test_render_whitespace() {
+        let mut input = Input::new("Title").show_whitespace(true).tab_width(2);
+
+        input.input = "a\tb  ".to_string();
+        input.cursor = 5;
+        assert_eq!(
+            "Title\n> a→ b·· \n",
+            without_ansi(input.render().unwrap().as_str())
+        );
+        assert_eq!(4, input.display_width(3));
+        assert_eq!("a\tb  ", input.input);
+
+        let input = Input::new("Title").tab_width(2);
+        assert_eq!(vec!["a", "\t", "b"], input.display_segments("a\tb"));
+    }
+
+    #[test]
+    fn test_word_navigation() {
+        let mut input = Input::new("Title");
+        input.input = "héllo wörld, again".to_string();
+        input.cursor = input.input.chars().count();
+
+        input.handle_word_left();
+        assert_eq!(13, input.cursor);
+        input.handle_word_left();
+        assert_eq!(6, input.cursor);
+        input.handle_word_left();
+        assert_eq!(0, input.cursor);
+        input.handle_word_right();
+        assert_eq!(5, input.cursor);
+        input.handle_word_right();
+        assert_eq!(11, input.cursor);
+    }
+
+    #[test]
+    fn test_render_counter() {
+        let mut input = Input::new("Title").counter_max(50);
+
+        input.input = "hello".to_string();
+        input.cursor = 5;
+        assert_eq!(
+            "Title\n> hello  5/50\n",
+            without_ansi(input.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!("(555) 123-4567", apply_mask("(___) ___-____", "5551234567"));
+        assert_eq!("(555) 1", apply_mask("(___) ___-____", "5551"));
+        assert_eq!("", apply_mask("(___) ___-____", ""));
+        assert_eq!(6, mask_column("(___) ___-____", 3));
+
+        let mut input = Input::new("Phone").mask_pattern("(___) ___-____");
+        for c in "(555) 1".chars() {
+            input.handle_key(c).unwrap();
+        }
+        assert_eq!("5551", input.input);
+        assert_eq!(
+            "Phone\n> (555) 1__-____\n",
+            without_ansi(input.render().unwrap().as_str())
+        );
+        assert_eq!("(555) 1", input.value());
+        assert_eq!("5551", input.mask_raw_value(true).value());
+    }
+
+    #[test]
+    fn test_max_length() {
+        let mut input = Input::new("Code").max_length(3).show_counter(true);
+        for c in "añbc".chars() {
+            input.handle_key(c).unwrap();
+        }
+        assert_eq!("añb", input.input);
+        assert_eq!(
+            "Code\n> añb  3/3\n",
+            without_ansi(input.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn test_validator() {
+        let input = Input::new("Port")
+            .validation(NON_EMPTY)
+            .validator(crate::validators::IntRange(1, 65535));
+        assert_eq!(
+            Err("Name cannot be empty".to_string()),
+            input.validate_value("")
+        );
+        assert_eq!(
+            Err("Enter a whole number".to_string()),
+            input.validate_value("http")
+        );
+        assert_eq!(Ok(()), input.validate_value("8080"));
+
+        let input = input
+            .add_validator(crate::validators::Length { min: 1, max: 4 })
+            .add_validator(|s: &str| match s.starts_with('0') {
+                true => Err("No leading zeros".to_string()),
+                false => Ok(()),
+            });
+        assert_eq!(
+            Err("Enter at most 4 characters".to_string()),
+            input.validate_value("00080")
+        );
+        assert_eq!(
+            Err("No leading zeros".to_string()),
+            input.validate_value("080")
+        );
+    }
+
+    #[test]
+    fn test_numeric() {
+        let mut input = Input::new("Count").numeric(true);
+        for c in "1a-2.".chars() {
+            input.handle_key(c).unwrap();
+        }
+        assert_eq!("12", input.input);
+
+        let mut input = Input::new("Price").decimal(true);
+        for c in "1.5.-0e".chars() {
+            input.handle_key(c).unwrap();
+        }
+        assert_eq!("1.50", input.input);
+        input.cursor = 0;
+        input.handle_key('-').unwrap();
+        input.handle_key('-').unwrap();
+        input.cursor = 0;
+        input.handle_key('9').unwrap();
+        assert_eq!("-1.50", input.input);
+    }
+
+    #[test]
+    fn test_numeric_suggestion() {
+        let mut input = Input::new("Count").numeric(true);
+        input.input = "1".to_string();
+        input.cursor = 1;
+        input.suggestion = Some("2ab3".to_string());
+        input.handle_tab().unwrap();
+        assert_eq!("12", input.input);
+        assert_eq!(2, input.cursor);
+
+        let input = Input::new("Count").numeric(true).decimal(false);
+        assert!(input.numeric && !input.decimal);
+    }
+
+    #[test]
+    fn test_render_without_cursor_block() {
+        let mut input = Input::new("Name").inline(true).show_cursor_block(false);
+        assert_eq!("Name> \n", without_ansi(input.render().unwrap().as_str()));
+        input.input = "Li".to_string();
+        input.cursor = 2;
+        assert_eq!("Name> Li\n", without_ansi(input.render().unwrap().as_str()));
+    }
+
+    #[test]
+    fn test_observe() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut input = Input::new("Password").password(true).observe(tx);
+        input.input = "hunter2".to_string();
+        input.handle_submit(Instant::now()).unwrap();
+        // the value is reported as displayed, so passwords stay masked without their length
+        let event = PromptEvent::Submitted {
+            title: "Password".to_string(),
+            value: "*".repeat(12),
+        };
+        assert_eq!(vec![event], rx.try_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut input = Input::new("Name");
+        input.input = "Li".to_string();
+        input.cursor = 2;
+        input.handle_arrow_left().unwrap();
+        input.handle_word_left();
+
+        let (value, metrics) = input.handle_submit(Instant::now()).unwrap();
+        assert_eq!("Li", value);
+        assert_eq!(2, metrics.navigations);
+    }
+
+    #[test]
+    fn test_on_input_change() {
+        let changes = std::cell::RefCell::new(vec![]);
+        let mut input = Input::new("Name")
+            .on_input_change(|value| changes.borrow_mut().push(value.to_string()));
+        for c in "Li".chars() {
+            let prev_input = Some(input.input.clone());
+            input.handle_key(c).unwrap();
+            input.notify_input_change(prev_input);
+        }
+        let prev_input = Some(input.input.clone());
+        input.handle_arrow_left().unwrap();
+        input.notify_input_change(prev_input);
+        drop(input);
+        assert_eq!(vec!["L", "Li"], changes.into_inner());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fixed_position() {
+        let path = std::env::temp_dir().join(format!("demand-fixed-{}", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let term = Term::read_write_pair(file.try_clone().unwrap(), file);
+        let mut input = Input::new("Name").term(term).fixed_position(true);
+        input.height = 3;
+        input.clear().unwrap();
+        input.handle_submit(Instant::now()).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // the input is redrawn from the saved position instead of clearing the lines above
+        assert!(written.starts_with("\x1b8\r\x1b[0J"));
+        assert!(written.ends_with("\x1b8"));
+        assert!(!written.contains("\x1b[1A"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_cursor() {
+        let path = std::env::temp_dir().join(format!("demand-cursor-{}", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let term = Term::read_write_pair(file.try_clone().unwrap(), file);
+        let mut input = Input::new("Name")
+            .description("Your name")
+            .prompt("> ")
+            .show_cursor_block(false)
+            .term(term);
+        input.input = "Li".to_string();
+        input.cursor = 1;
+        let output = input.render().unwrap();
+        assert_eq!(1, input.set_cursor(&output).unwrap());
+        input.err = Some("Too short".to_string());
+        let output = input.render().unwrap();
+        assert_eq!(3, input.set_cursor(&output).unwrap());
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // up to the input line, then past the prompt to the cursor
+        assert_eq!("\x1b[1A\x1b[3C\x1b[3A\x1b[3C", written);
+    }
 }
@@ -0,0 +1,150 @@
+use std::borrow::Cow;
+use std::io;
+use std::io::Write;
+
+use console::Term;
+use termcolor::{Buffer, WriteColor};
+
+use crate::theme::Theme;
+use crate::{term, theme};
+
+/// Aligned key-value pairs, for printing summaries between prompts
+///
+/// # Example
+/// ```rust
+/// use demand::KeyValue;
+///
+/// KeyValue::new()
+///     .title("Summary")
+///     .row("Name", "Ferris")
+///     .row("Country", "Germany")
+///     .run()
+///     .unwrap();
+/// ```
+pub struct KeyValue<'a> {
+    /// The title printed above the pairs
+    pub title: String,
+    /// The colors/style of the pairs
+    pub theme: Cow<'a, Theme>,
+
+    rows: Vec<(String, String)>,
+    term: Term,
+}
+
+impl<'a> KeyValue<'a> {
+    /// Create an empty list of pairs
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            theme: Cow::Owned(theme::default_theme()),
+            rows: Vec::new(),
+            term: term::default_term(),
+        }
+    }
+
+    /// Set the title printed above the pairs, which are then indented under it
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Add a pair
+    pub fn row<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.rows.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add multiple pairs
+    pub fn rows<K: ToString, V: ToString>(
+        mut self,
+        rows: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        for (key, value) in rows {
+            self = self.row(key, value);
+        }
+        self
+    }
+
+    /// Set the theme of the pairs
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Prints the pairs
+    pub fn run(mut self) -> io::Result<()> {
+        let output = self.render()?;
+        self.term.write_all(output.as_bytes())?;
+        self.term.flush()
+    }
+
+    fn render(&self) -> io::Result<String> {
+        let mut out = Buffer::ansi();
+        let indent = match self.title.is_empty() {
+            true => "",
+            false => "  ",
+        };
+        if !self.title.is_empty() {
+            out.set_color(&self.theme.title)?;
+            writeln!(out, "{}", self.title)?;
+        }
+        let key_width = self
+            .rows
+            .iter()
+            .map(|(key, _)| console::measure_text_width(key))
+            .max()
+            .unwrap_or(0);
+        for (key, value) in &self.rows {
+            out.set_color(&self.theme.description)?;
+            write!(
+                out,
+                "{indent}{}  ",
+                console::pad_str(key, key_width, console::Alignment::Left, None)
+            )?;
+            out.set_color(&self.theme.selected_option)?;
+            for (i, line) in value.lines().enumerate() {
+                match i {
+                    0 => writeln!(out, "{line}")?,
+                    _ => writeln!(out, "{indent}{}  {line}", " ".repeat(key_width))?,
+                }
+            }
+            if value.is_empty() {
+                writeln!(out)?;
+            }
+        }
+
+        out.reset()?;
+        Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
+    }
+}
+
+impl Default for KeyValue<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::without_ansi;
+
+    #[test]
+    fn test_render() {
+        let table = KeyValue::new()
+            .title("Summary")
+            .row("Name", "Ferris")
+            .row("Country", "Germany")
+            .row("Toppings", "Lettuce\nCheese");
+        assert_eq!(
+            "Summary\n  Name      Ferris\n  Country   Germany\n  Toppings  Lettuce\n            Cheese\n",
+            without_ansi(table.render().unwrap().as_str())
+        );
+
+        let table = KeyValue::new().rows([("a", 1), ("bcd", 2)]);
+        assert_eq!(
+            "a    1\nbcd  2\n",
+            without_ansi(table.render().unwrap().as_str())
+        );
+    }
+}
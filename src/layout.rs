@@ -0,0 +1,187 @@
+/// Width constraint and horizontal placement applied to a rendered prompt
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Layout {
+    /// Wrap lines longer than this many columns
+    pub max_width: Option<usize>,
+    /// Center the wrapped block in the terminal
+    pub center: bool,
+}
+
+impl Layout {
+    /// Wrap and offset every line of `output` for a terminal `term_width` columns wide
+    pub fn apply(&self, output: String, term_width: usize) -> String {
+        if self.max_width.is_none() && !self.center {
+            return output;
+        }
+        let width = self.max_width.unwrap_or(term_width).min(term_width).max(1);
+        let rows = output
+            .split_inclusive('\n')
+            .flat_map(|line| match line.strip_suffix('\n') {
+                Some(line) => wrap(line, width)
+                    .into_iter()
+                    .map(|row| row + "\n")
+                    .collect::<Vec<_>>(),
+                None => wrap(line, width),
+            })
+            .collect::<Vec<_>>();
+        if !self.center {
+            return rows.concat();
+        }
+        let block_width = rows
+            .iter()
+            .map(|row| console::measure_text_width(row))
+            .max()
+            .unwrap_or(0);
+        let pad = " ".repeat(term_width.saturating_sub(block_width) / 2);
+        rows.into_iter()
+            .map(|row| match row.as_str() {
+                "" | "\n" => row,
+                _ => format!("{pad}{row}"),
+            })
+            .collect()
+    }
+
+    /// The row that line `row` of `output` starts on once `apply` has wrapped it
+    pub fn row(&self, output: &str, row: usize, term_width: usize) -> usize {
+        if self.max_width.is_none() && !self.center {
+            return row;
+        }
+        let width = self.max_width.unwrap_or(term_width).min(term_width).max(1);
+        output
+            .lines()
+            .take(row)
+            .map(|line| wrap(line, width).len())
+            .sum()
+    }
+}
+
+/// Split `line` into rows at most `width` columns wide, breaking at the last space where possible
+///
+/// Escape sequences are copied through without taking up columns.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut rows = vec![];
+    let mut row = String::new();
+    let mut col = 0;
+    // byte offset of the last space in `row` and the column just after it
+    let mut last_space: Option<(usize, usize)> = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            row.push(c);
+            match chars.next() {
+                Some('[') => {
+                    row.push('[');
+                    for c in chars.by_ref() {
+                        row.push(c);
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                Some(']') => {
+                    row.push(']');
+                    while let Some(c) = chars.next() {
+                        row.push(c);
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            row.push(chars.next().unwrap());
+                            break;
+                        }
+                    }
+                }
+                Some(c) => row.push(c),
+                None => {}
+            }
+            continue;
+        }
+        let w = console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+        if c == ' ' && col + w > width {
+            rows.push(std::mem::take(&mut row));
+            col = 0;
+            last_space = None;
+            continue;
+        }
+        if col + w > width && col > 0 {
+            match last_space.take() {
+                Some((idx, space_col)) => {
+                    let rest = row.split_off(idx + 1);
+                    row.pop();
+                    rows.push(std::mem::replace(&mut row, rest));
+                    col -= space_col;
+                }
+                None => {
+                    rows.push(std::mem::take(&mut row));
+                    col = 0;
+                }
+            }
+        }
+        if c == ' ' {
+            last_space = Some((row.len(), col + 1));
+        }
+        row.push(c);
+        col += w;
+    }
+    rows.push(row);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        let layout = Layout {
+            max_width: Some(10),
+            center: false,
+        };
+        assert_eq!(
+            "Pick a\ncountry to\nvisit\n> Germany\n",
+            layout.apply("Pick a country to visit\n> Germany\n".to_string(), 80)
+        );
+        assert_eq!(
+            "\x1b[1mabcdefghij\nkl\x1b[0m\n",
+            layout.apply("\x1b[1mabcdefghijkl\x1b[0m\n".to_string(), 80)
+        );
+    }
+
+    #[test]
+    fn test_wrap_hyperlink() {
+        let layout = Layout {
+            max_width: Some(10),
+            center: false,
+        };
+        let link = "\x1b]8;;https://docs.rs\x1b\\docs\x1b]8;;\x1b\\";
+        let output = format!("read the {link}\n");
+        assert_eq!(format!("read the\n{link}\n"), layout.apply(output, 80));
+    }
+
+    #[test]
+    fn test_center() {
+        let layout = Layout {
+            max_width: Some(6),
+            center: true,
+        };
+        assert_eq!(
+            "   Title\n   > a b\n   c\n\n",
+            layout.apply("Title\n> a b c\n\n".to_string(), 12)
+        );
+    }
+
+    #[test]
+    fn test_row() {
+        let layout = Layout {
+            max_width: Some(10),
+            center: false,
+        };
+        let output = "Pick a country to visit\n> Germany\n";
+        assert_eq!(3, layout.row(output, 1, 80));
+        assert_eq!(
+            Some("> Germany"),
+            layout.apply(output.to_string(), 80).lines().nth(3)
+        );
+        assert_eq!(1, Layout::default().row(output, 1, 80));
+    }
+}
@@ -1,28 +1,68 @@
 //! A prompt library for Rust. Based on [huh? for Go](https://github.com/charmbracelet/huh).
 
+pub use cancel::CancelToken;
 pub use confirm::Confirm;
+/// A key press, as passed to the `on_key` hooks
+///
+/// This is the `console` crate's key type re-exported as is, so it changes with the version of
+/// `console` demand depends on.
+pub use console::Key;
 pub use dialog::Dialog;
 pub use dialog::DialogButton;
+pub use divider::Divider;
+pub use event::PromptEvent;
 pub use input::Input;
+pub use input::InputValidator;
+pub use key_value::KeyValue;
 pub use list::List;
+pub use metrics::PromptMetrics;
 pub use multiselect::MultiSelect;
 pub use option::DemandOption;
+pub use option::DescriptionLayout;
+pub use outcome::Outcome;
+pub use outcome::SuccessShows;
+pub use pause::Pause;
+pub use result::PromptResult;
+pub use select::KeyOutcome;
 pub use select::Select;
 pub use spinner::Spinner;
 pub use spinner::SpinnerStyle;
+pub use term::is_interactive;
+pub use term::last_height;
+pub use term::set_term;
+pub use theme::set_ascii;
+pub use theme::set_default_theme;
 pub use theme::Theme;
+pub use truncate::Truncation;
 
+mod cancel;
 mod confirm;
 #[cfg_attr(any(windows), path = "ctrlc_stub.rs")]
 mod ctrlc;
 mod dialog;
+mod divider;
+mod event;
+mod help;
 mod input;
+mod key_value;
+mod layout;
 mod list;
+mod metrics;
 mod multiselect;
 mod option;
+mod outcome;
+mod pause;
+#[cfg_attr(any(windows), path = "poll_stub.rs")]
+mod poll;
+mod rank;
+mod remember;
+mod result;
 mod select;
 mod spinner;
+mod term;
 mod theme;
+mod truncate;
+pub mod validators;
 
 #[cfg(test)]
 mod test;
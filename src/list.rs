@@ -1,10 +1,16 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io;
+use std::time::Duration;
 
 use console::{Key, Term};
 use std::io::Write;
 use termcolor::{Buffer, WriteColor};
 
-use crate::{ctrlc, theme, Theme};
+use crate::cancel::{self, CancelToken};
+use crate::help::FooterFn;
+use crate::outcome::{self, Outcome, SuccessFormatFn};
+use crate::{ctrlc, help, poll, term, theme, PromptResult, Theme};
 
 /// Display a list of options
 ///
@@ -44,18 +50,31 @@ pub struct List<'a> {
     /// Number of items to show on each page
     pub success_items: usize,
     /// Colors/style of the input
-    pub theme: &'a Theme,
+    pub theme: Cow<'a, Theme>,
 
     term: Term,
+    cancel_token: Option<CancelToken>,
     items: Vec<&'a str>,
+    links: HashMap<&'a str, String>,
     capacity: usize,
     filtering: bool,
     filterable: bool,
     filter: String,
+    // the lowercased filter the items are currently filtered by, which lags behind `filter`
+    // while a debounce is pending
+    applied_filter: String,
+    matches: HashMap<String, Vec<usize>>,
+    debounce: Option<Duration>,
     cur_page: usize,
     height: usize,
     pages: usize,
     scroll: usize,
+    help_overlay: bool,
+    showing_help: bool,
+    footer: Option<FooterFn<'a>>,
+    show_position: bool,
+    success_format: Option<SuccessFormatFn<'a>>,
+    tee: Option<Box<dyn Write + 'a>>,
 }
 
 impl<'a> List<'a> {
@@ -64,21 +83,31 @@ impl<'a> List<'a> {
         let mut s = Self {
             title: title.into(),
             description: String::new(),
-            theme: &theme::DEFAULT,
+            theme: Cow::Owned(theme::default_theme()),
             items: Vec::new(),
-            term: Term::stderr(),
+            links: HashMap::new(),
+            term: term::default_term(),
+            cancel_token: None,
             capacity: 0,
             filtering: false,
             filterable: false,
             filter: String::new(),
+            applied_filter: String::new(),
+            matches: HashMap::new(),
+            debounce: None,
             height: 0,
             cur_page: 0,
             pages: 0,
             success_items: 4,
             scroll: 0,
+            help_overlay: false,
+            showing_help: false,
+            footer: None,
+            show_position: false,
+            success_format: None,
+            tee: None,
         };
-        let max_height = s.term.size().0 as usize;
-        s.capacity = max_height.max(8) - 5;
+        s.fit_capacity();
         s
     }
 
@@ -102,6 +131,15 @@ impl<'a> List<'a> {
         self
     }
 
+    /// Adds an item to the list that is rendered as a hyperlink to `link` in terminals that
+    /// support it
+    ///
+    /// The link is only emitted when colors are enabled.
+    pub fn item_link(mut self, entry: &'a str, link: &str) -> Self {
+        self.links.insert(entry, link.to_string());
+        self.item(entry)
+    }
+
     /// Sets the number of items to show on confirmation
     pub fn success_items(mut self, items: usize) -> Self {
         self.success_items = items;
@@ -114,18 +152,83 @@ impl<'a> List<'a> {
         self
     }
 
+    /// Sets how long typing must pause before the items are filtered again
+    ///
+    /// Useful for lists of thousands of items, where filtering on every keystroke is noticeable.
+    /// On Windows, where key reads can't time out, the items are filtered on every keystroke.
+    pub fn filter_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = Some(debounce);
+        self
+    }
+
+    /// Sets a static line to display beneath the help keys
+    pub fn footer(mut self, footer: &str) -> Self {
+        let footer = footer.to_string();
+        self.footer = Some(Box::new(move || footer.clone()));
+        self
+    }
+
+    /// Sets a status line to display beneath the help keys, recomputed each time it is drawn
+    pub fn status<F: Fn() -> String + 'a>(mut self, status: F) -> Self {
+        self.footer = Some(Box::new(status));
+        self
+    }
+
+    /// Sets whether to show the range of visible items, e.g. "items 21–40 of 63", instead of the
+    /// page number
+    pub fn show_position(mut self, show_position: bool) -> Self {
+        self.show_position = show_position;
+        self
+    }
+
+    /// Sets whether pressing `?` shows an overlay listing every keybinding
+    pub fn enable_help_overlay(mut self, help_overlay: bool) -> Self {
+        self.help_overlay = help_overlay;
+        self
+    }
+
+    /// Cancel the list with an `Interrupted` error once `token` is cancelled, e.g. from another
+    /// thread
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
     /// Sets the theme of the list
-    pub fn theme(mut self, theme: &'a Theme) -> Self {
-        self.theme = theme;
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Set how the line shown after submitting is formatted, replacing the default "Title value"
+    ///
+    /// Return an empty string to show nothing.
+    pub fn success_format(mut self, format: impl Fn(&Outcome) -> String + 'a) -> Self {
+        self.success_format = Some(Box::new(format));
+        self
+    }
+
+    /// Also write the line shown after submitting to `sink`, without colors
+    ///
+    /// Errors writing to `sink` are ignored.
+    pub fn tee(mut self, sink: Box<dyn Write + 'a>) -> Self {
+        self.tee = Some(sink);
         self
     }
 
+    /// Displays the list like `run`, returning whether it was submitted, cancelled or failed
+    pub fn run_result(self) -> PromptResult<()> {
+        self.run().into()
+    }
+
     /// Displays the input to the user and returns the response
     ///
     /// This function will block until the user submits the input. If the user cancels the input,
     /// an error of type `io::ErrorKind::Interrupted` is returned.
     pub fn run(mut self) -> Result<(), io::Error> {
         let ctrlc_handle = ctrlc::show_cursor_after_ctrlc(&self.term)?;
+        self.fit_capacity();
+        self.pages = self.get_pages();
 
         loop {
             self.clear()?;
@@ -134,7 +237,20 @@ impl<'a> List<'a> {
             self.term.flush()?;
             self.height = output.lines().count() - 1;
             if self.filtering {
-                match self.term.read_key()? {
+                let key = match self
+                    .debounce()
+                    .filter(|_| self.applied_filter != self.query())
+                {
+                    Some(debounce) => {
+                        cancel::read_key_timeout(&self.term, self.cancel_token.as_ref(), debounce)?
+                    }
+                    None => Some(cancel::read_key(&self.term, self.cancel_token.as_ref())?),
+                };
+                let Some(key) = key else {
+                    self.apply_filter();
+                    continue;
+                };
+                match key {
                     Key::Enter => self.handle_stop_filtering(true)?,
                     Key::Escape => self.handle_stop_filtering(false)?,
                     Key::Backspace => self.handle_filter_backspace()?,
@@ -143,12 +259,18 @@ impl<'a> List<'a> {
                 }
             } else {
                 self.term.hide_cursor()?;
-                match self.term.read_key()? {
+                let key = cancel::read_key(&self.term, self.cancel_token.as_ref())?;
+                if self.showing_help {
+                    self.showing_help = false;
+                    continue;
+                }
+                match key {
                     Key::ArrowUp | Key::Char('k') => self.handle_up(),
                     Key::ArrowDown | Key::Char('j') => self.handle_down()?,
                     Key::ArrowLeft | Key::Char('h') => self.handle_left()?,
                     Key::ArrowRight | Key::Char('l') => self.handle_right()?,
                     Key::Char('/') if self.filterable => self.handle_start_filtering(),
+                    Key::Char('?') if self.help_overlay => self.showing_help = true,
                     Key::Escape => {
                         self.term.show_cursor()?;
                         ctrlc_handle.close();
@@ -160,6 +282,8 @@ impl<'a> List<'a> {
                         ctrlc_handle.close();
                         let output = self.render_success()?;
                         self.term.write_all(output.as_bytes())?;
+                        term::record_height(&self.term, &output);
+                        term::tee(&mut self.tee, &output);
                         return Ok(());
                     }
                     _ => {}
@@ -210,32 +334,72 @@ impl<'a> List<'a> {
         self.cur_page = 0;
         if !save {
             self.filter.clear();
-            self.pages = self.get_pages();
         }
+        self.apply_filter();
         self.term.clear_to_end_of_screen()
     }
 
     fn handle_filter_backspace(&mut self) -> Result<(), io::Error> {
         self.filter.pop();
-        self.scroll = 0;
-        self.pages = self.get_pages();
+        self.handle_filter_change();
         self.term.clear_to_end_of_screen()
     }
 
     fn handle_filter_key(&mut self, key: char) -> Result<(), io::Error> {
         self.filter.push(key);
+        self.handle_filter_change();
+        self.term.clear_to_end_of_screen()
+    }
+
+    fn handle_filter_change(&mut self) {
+        if self.debounce().is_none() {
+            self.apply_filter();
+        }
+    }
+
+    /// The debounce to wait for, or `None` when key reads can't time out
+    fn debounce(&self) -> Option<Duration> {
+        self.debounce.filter(|_| poll::HAS_TIMEOUT)
+    }
+
+    fn query(&self) -> String {
+        self.filter.to_lowercase()
+    }
+
+    /// Filters the items by the current filter, reusing the matches of earlier queries
+    fn apply_filter(&mut self) {
+        let query = self.query();
+        if !query.is_empty() && !self.matches.contains_key(&query) {
+            // a query containing the previous one can only match items the previous one did
+            let candidates = match self.matches.get(&self.applied_filter) {
+                Some(matches) if query.contains(&self.applied_filter) => matches.clone(),
+                _ => (0..self.items.len()).collect(),
+            };
+            let matches = candidates
+                .into_iter()
+                .filter(|&i| self.items[i].to_lowercase().contains(&query))
+                .collect();
+            self.matches.insert(query.clone(), matches);
+        }
+        self.applied_filter = query;
         self.scroll = 0;
         self.pages = self.get_pages();
-        self.term.clear_to_end_of_screen()
     }
 
     fn filtered_entries(&self) -> Vec<&&'a str> {
-        self.items
-            .iter()
-            .filter(|e| {
-                self.filter.is_empty() || e.to_lowercase().contains(&self.filter.to_lowercase())
-            })
-            .collect()
+        match self.matches.get(&self.applied_filter) {
+            Some(matches) => matches.iter().map(|&i| &self.items[i]).collect(),
+            None => self.items.iter().collect(),
+        }
+    }
+
+    /// The rows drawn besides the items: the title, description, filter, help and footer lines
+    fn chrome_rows(&self) -> usize {
+        4 + self.description.lines().count().max(1) + self.footer.is_some() as usize
+    }
+
+    fn fit_capacity(&mut self) {
+        self.capacity = term::fit_capacity(&self.term, self.chrome_rows());
     }
 
     fn get_pages(&self) -> usize {
@@ -258,6 +422,9 @@ impl<'a> List<'a> {
     }
 
     fn render(&self) -> Result<String, io::Error> {
+        if self.showing_help {
+            return help::render_overlay(&self.theme, &self.title, &self.help_overlay_keys());
+        }
         let mut out = Buffer::ansi();
 
         out.set_color(&self.theme.title)?;
@@ -269,11 +436,25 @@ impl<'a> List<'a> {
             write!(out, "{}", self.description)?;
             writeln!(out)?;
         }
-        for entry in self.visible_entries().iter() {
+        let visible_entries = self.visible_entries();
+        for entry in visible_entries.iter() {
             out.set_color(&self.theme.unselected_option)?;
-            writeln!(out, "  {entry}")?;
+            let link = self.links.get(**entry).map(String::as_str);
+            writeln!(out, "  {}", term::hyperlink(entry, link))?;
         }
-        if self.pages > 1 {
+        if self.show_position {
+            if !visible_entries.is_empty() {
+                let first = self.cur_page * self.capacity + self.scroll + 1;
+                let last = first + visible_entries.len() - 1;
+                let total = self.filtered_entries().len();
+                out.set_color(&self.theme.description)?;
+                writeln!(
+                    out,
+                    " items {first}{}{last} of {total}",
+                    theme::glyph("–", "-")
+                )?;
+            }
+        } else if self.pages > 1 {
             out.set_color(&self.theme.description)?;
             writeln!(out, " (page {}/{})", self.cur_page + 1, self.pages)?;
         }
@@ -288,6 +469,10 @@ impl<'a> List<'a> {
             out.set_color(&self.theme.description)?;
             write!(out, "/{}", self.filter)?;
         }
+        let start_col = match self.filtering || self.filter.is_empty() {
+            true => 0,
+            false => 1 + console::measure_text_width(&self.filter),
+        };
         let mut help_keys = vec![("↑/↓/k/j", "up/down")];
         if self.pages > 1 {
             help_keys.push(("←/→/h/l", "prev/next page"));
@@ -300,26 +485,45 @@ impl<'a> List<'a> {
             }
         }
         if !self.filtering {
-            help_keys.push(("enter", "done"));
-        }
-        for (i, (key, desc)) in help_keys.iter().enumerate() {
-            if i > 0 || (!self.filtering && !self.filter.is_empty()) {
-                out.set_color(&self.theme.help_sep)?;
-                write!(out, " • ")?;
+            if self.help_overlay {
+                help_keys.push(("?", "help"));
             }
-            out.set_color(&self.theme.help_key)?;
-            write!(out, "{key}")?;
-            out.set_color(&self.theme.help_desc)?;
-            write!(out, " {desc}")?;
+            help_keys.push(("enter", "done"));
         }
+        help::render_keys(
+            &mut out,
+            &self.theme,
+            &help_keys,
+            !self.filtering && !self.filter.is_empty(),
+            start_col,
+            self.term.size().1 as usize,
+        )?;
 
         writeln!(out)?;
 
         out.reset()?;
+        help::render_footer(&mut out, &self.theme, &self.footer)?;
         Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
     }
 
+    fn help_overlay_keys(&self) -> Vec<(&'static str, &'static str)> {
+        let mut keys = vec![("↑/↓/k/j", "up/down"), ("←/→/h/l", "prev/next page")];
+        if self.filterable {
+            keys.push(("/", "filter"));
+        }
+        keys.push(("esc", "cancel"));
+        keys.push(("enter", "done"));
+        keys
+    }
+
     fn render_success(&self) -> Result<String, io::Error> {
+        if let Some(format) = &self.success_format {
+            let outcome = Outcome {
+                title: &self.title,
+                values: self.items.clone(),
+            };
+            return Ok(outcome::render(format, &outcome));
+        }
         let mut out = Buffer::ansi();
 
         out.set_color(&self.theme.title)?;
@@ -380,4 +584,36 @@ mod tests {
             without_ansi(list.render().unwrap().as_str())
         )
     }
+
+    #[test]
+    fn test_render_position() {
+        let mut list = List::new("Foods")
+            .show_position(true)
+            .items(&["chips", "burger", "sandwich", "cupcakes"]);
+        list.capacity = 2;
+        list.scroll = 1;
+
+        assert_eq!(
+            "Foods\n  burger\n  sandwich\n items 2–3 of 4\n↑/↓/k/j up/down • enter done\n",
+            without_ansi(list.render().unwrap().as_str())
+        )
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut list = List::new("Foods")
+            .filter_debounce(Duration::from_millis(100))
+            .items(&["chips", "burger", "sandwich", "cupcakes"]);
+        list.filter = "CH".to_string();
+        list.handle_filter_change();
+        assert_eq!(4, list.filtered_entries().len());
+        list.apply_filter();
+        assert_eq!(vec![&"chips", &"sandwich"], list.filtered_entries());
+        list.filter = "chi".to_string();
+        list.apply_filter();
+        assert_eq!(vec![&"chips"], list.filtered_entries());
+        list.filter.pop();
+        list.apply_filter();
+        assert_eq!(vec![&"chips", &"sandwich"], list.filtered_entries());
+    }
 }
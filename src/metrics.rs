@@ -0,0 +1,14 @@
+use std::time::Duration;
+
+/// Counters describing how the user interacted with a prompt, returned by `run_with_metrics`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PromptMetrics {
+    /// Time from the prompt first being drawn until it was submitted
+    pub elapsed: Duration,
+    /// Number of keys pressed
+    pub keystrokes: usize,
+    /// Whether the user started filtering the options
+    pub filtered: bool,
+    /// Number of times the user moved the cursor or changed page
+    pub navigations: usize,
+}
@@ -1,15 +1,30 @@
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::HashSet;
+use std::fmt::Display;
 use std::io;
-use std::io::Write;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::Instant;
 
-use console::{Alignment, Key, Term};
+use console::{Key, Term};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use itertools::Itertools;
 use termcolor::{Buffer, WriteColor};
 
+use crate::cancel::{self, CancelToken};
+use crate::help::FooterFn;
+use crate::layout::Layout;
+use crate::outcome::{self, Outcome, SuccessFormatFn, SuccessShows};
+use crate::remember::{self, Remembered};
+use crate::select::{self, FilterChangeFn, KeyOutcome, OnKeyFn, CTRL_D, CTRL_U};
 use crate::theme::Theme;
-use crate::{ctrlc, theme, DemandOption};
+use crate::{
+    ctrlc, event, help, option, rank, term, theme, DemandOption, DescriptionLayout, PromptEvent,
+    PromptMetrics, PromptResult,
+};
 
 /// Select multiple options from a list
 ///
@@ -45,7 +60,7 @@ pub struct MultiSelect<'a, T> {
     /// The title of the selector
     pub title: String,
     /// The colors/style of the selector
-    pub theme: &'a Theme,
+    pub theme: Cow<'a, Theme>,
     /// A description to display above the selector
     pub description: String,
     /// The options which can be selected
@@ -67,12 +82,61 @@ pub struct MultiSelect<'a, T> {
     cursor: usize,
     height: usize,
     term: Term,
+    cancel_token: Option<CancelToken>,
     pages: usize,
     cur_page: usize,
     capacity: usize,
     fuzzy_matcher: SkimMatcherV2,
+    help_overlay: bool,
+    back_hint: bool,
+    show_system_cursor: bool,
+    focused_row: Cell<Option<usize>>,
+    escape_clears_filter: bool,
+    vim_keys: Option<bool>,
+    showing_help: bool,
+    selected_prefix: Option<String>,
+    unselected_prefix: Option<String>,
+    observer: Option<Sender<PromptEvent>>,
+    success_format: Option<SuccessFormatFn<'a>>,
+    success_item: Option<fn(&T) -> String>,
+    tee: Option<Box<dyn Write + 'a>>,
+    keep_focus_on_clear: bool,
+    footer: Option<FooterFn<'a>>,
+    option_filter: Option<OptionFilterFn<'a, T>>,
+    preserve_below: bool,
+    on_key: Option<OnKeyFn<'a, T>>,
+    on_toggle: Option<OnToggleFn<'a, T>>,
+    error_line: bool,
+    metrics: PromptMetrics,
+    remember: Option<PathBuf>,
+    stable_filter: bool,
+    pin_selected: bool,
+    enter_selects_focused: bool,
+    scroll_indicators: bool,
+    submit_key: Key,
+    submit_key_name: String,
+    confirm_submit: bool,
+    pending_submit: bool,
+    group_limits: Vec<(String, usize, usize)>,
+    fixed_position: bool,
+    compact: bool,
+    description_layout: DescriptionLayout,
+    selection_order: Vec<usize>,
+    in_selection_order: bool,
+    keep_on_cancel: bool,
+    layout: Layout,
+    on_filter_change: Option<FilterChangeFn<'a, T>>,
+    filter_replaced: bool,
+    // the user's own options while `on_filter_change` has replaced them, restored once the filter
+    // is cleared or the selection is returned
+    original_options: Option<Vec<DemandOption<T>>>,
 }
 
+type OptionFilterFn<'a, T> = Box<dyn Fn(&DemandOption<T>, &[DemandOption<T>]) -> bool + 'a>;
+type OnToggleFn<'a, T> = Box<dyn FnMut(&DemandOption<T>, bool) + 'a>;
+/// The selected options with their positions, the indeterminate options and the metrics
+type Submitted<T> = (Vec<(usize, T)>, Vec<T>, PromptMetrics);
+
 impl<'a, T> MultiSelect<'a, T> {
     /// Create a new multi select with the given title
     pub fn new<S: Into<String>>(title: S) -> Self {
@@ -83,22 +147,63 @@ impl<'a, T> MultiSelect<'a, T> {
             min: 0,
             max: usize::MAX,
             filterable: false,
-            theme: &theme::DEFAULT,
+            theme: Cow::Owned(theme::default_theme()),
             cursor_x: 0,
             cursor_y: 0,
             err: None,
             cursor: 0,
             height: 0,
-            term: Term::stderr(),
+            term: term::default_term(),
+            cancel_token: None,
             filter: String::new(),
             filtering: false,
             pages: 0,
             cur_page: 0,
             capacity: 0,
             fuzzy_matcher: SkimMatcherV2::default().use_cache(true).smart_case(),
+            help_overlay: false,
+            back_hint: false,
+            show_system_cursor: false,
+            focused_row: Cell::new(None),
+            escape_clears_filter: true,
+            vim_keys: None,
+            showing_help: false,
+            selected_prefix: None,
+            unselected_prefix: None,
+            observer: None,
+            success_format: None,
+            success_item: None,
+            tee: None,
+            keep_focus_on_clear: false,
+            footer: None,
+            option_filter: None,
+            preserve_below: false,
+            on_key: None,
+            on_toggle: None,
+            error_line: false,
+            metrics: PromptMetrics::default(),
+            remember: None,
+            stable_filter: false,
+            pin_selected: false,
+            enter_selects_focused: false,
+            scroll_indicators: false,
+            submit_key: Key::Enter,
+            submit_key_name: help::key_name(&Key::Enter),
+            confirm_submit: false,
+            pending_submit: false,
+            group_limits: vec![],
+            fixed_position: false,
+            compact: false,
+            description_layout: DescriptionLayout::default(),
+            selection_order: vec![],
+            in_selection_order: false,
+            keep_on_cancel: false,
+            layout: Layout::default(),
+            on_filter_change: None,
+            filter_replaced: false,
+            original_options: None,
         };
-        let max_height = ms.term.size().0 as usize;
-        ms.capacity = max_height.max(8) - 6;
+        ms.fit_capacity();
         ms
     }
 
@@ -115,6 +220,21 @@ impl<'a, T> MultiSelect<'a, T> {
         self
     }
 
+    /// Add multiple options to the selector from `(label, item)` pairs, in order
+    pub fn options_from<I, S>(mut self, options: I) -> Self
+    where
+        I: IntoIterator<Item = (S, T)>,
+        S: Into<String>,
+    {
+        self.options.extend(
+            options
+                .into_iter()
+                .map(|(label, item)| DemandOption::with_label(label, item)),
+        );
+        self.pages = self.get_pages();
+        self
+    }
+
     /// Add multiple options to the selector
     pub fn options(mut self, options: Vec<DemandOption<T>>) -> Self {
         for option in options {
@@ -124,6 +244,25 @@ impl<'a, T> MultiSelect<'a, T> {
         self
     }
 
+    /// Set the description of each option added so far from `describe`
+    ///
+    /// Options for which `describe` returns `None` keep their current description.
+    pub fn describe_with(mut self, describe: impl Fn(&DemandOption<T>) -> Option<String>) -> Self {
+        for option in &mut self.options {
+            if let Some(description) = describe(option) {
+                option.description = Some(description);
+            }
+        }
+        self
+    }
+
+    /// Mutable access to the options of the selector
+    ///
+    /// Paging is recomputed when the selector runs.
+    pub fn options_mut(&mut self) -> &mut Vec<DemandOption<T>> {
+        &mut self.options
+    }
+
     /// Set the minimum number of options which must be selected
     pub fn min(mut self, min: usize) -> Self {
         self.min = min;
@@ -136,6 +275,17 @@ impl<'a, T> MultiSelect<'a, T> {
         self
     }
 
+    /// Set the minimum and maximum number of options which must be selected from `group`
+    ///
+    /// Options join a group with `DemandOption::group`. The limits are checked on submit in
+    /// addition to `min` and `max`. The minimum is lowered to the number of options in the group
+    /// that can be selected, so a group that is empty or only has disabled options never blocks
+    /// submitting.
+    pub fn group_limits(mut self, group: &str, min: usize, max: usize) -> Self {
+        self.group_limits.push((group.to_string(), min, max));
+        self
+    }
+
     /// Set whether the selector can be filtered with a query
     pub fn filterable(mut self, filterable: bool) -> Self {
         self.filterable = filterable;
@@ -154,155 +304,852 @@ impl<'a, T> MultiSelect<'a, T> {
         self
     }
 
+    /// Open the selector already filtering by `filter` with the cursor at its end, as if the user
+    /// had typed it
+    pub fn start_filtering_with(self, filter: &str) -> Self {
+        self.filterable(true).filtering(true).filter(filter)
+    }
+
+    /// Only show options for which `predicate` returns true, re-evaluated on every render
+    ///
+    /// The predicate also receives all options so visibility can depend on what else is
+    /// selected. Hidden options cannot be navigated to and are left out of the result.
+    pub fn filter_options<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&DemandOption<T>, &[DemandOption<T>]) -> bool + 'a,
+    {
+        self.option_filter = Some(Box::new(predicate));
+        self
+    }
+
+    /// Set the prefix shown before selected options, overriding the theme
+    pub fn selected_prefix(mut self, prefix: &str) -> Self {
+        self.selected_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Set the prefix shown before unselected options, overriding the theme
+    pub fn unselected_prefix(mut self, prefix: &str) -> Self {
+        self.unselected_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Report the outcome of the prompt to `observer` in addition to returning it
+    pub fn observe(mut self, observer: Sender<PromptEvent>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Set whether content printed below the prompt is kept on submit instead of being cleared
+    pub fn preserve_below(mut self, preserve_below: bool) -> Self {
+        self.preserve_below = preserve_below;
+        self
+    }
+
+    /// Set a hook called with the filter query each time it changes
+    ///
+    /// Returning options replaces the selector's options, e.g. with results fetched for the query.
+    /// Once it has replaced them, they are shown as returned, without local fuzzy filtering, until
+    /// the filter is cleared and the original options come back. Selections carry over between
+    /// the two by label, and options selected from the hook's results are added to the original
+    /// ones, so nothing selected is lost.
+    /// Returning `None` keeps the current options.
+    pub fn on_filter_change<F>(mut self, on_filter_change: F) -> Self
+    where
+        F: FnMut(&str) -> Option<Vec<DemandOption<T>>> + 'a,
+    {
+        self.on_filter_change = Some(Box::new(on_filter_change));
+        self
+    }
+
+    /// Set a hook called with keys the selector doesn't handle itself
+    ///
+    /// The hook can change the options, e.g. to refresh them, and should then return
+    /// `KeyOutcome::Rerender`.
+    pub fn on_key<F>(mut self, on_key: F) -> Self
+    where
+        F: FnMut(Key, &mut Vec<DemandOption<T>>) -> KeyOutcome + 'a,
+    {
+        self.on_key = Some(Box::new(on_key));
+        self
+    }
+
+    /// Set a hook called with an option and whether it is now selected each time it is toggled
+    ///
+    /// The hook runs as soon as the option changes, e.g. to apply a setting live, and the
+    /// selector still returns every selected option on submit.
+    pub fn on_toggle<F>(mut self, on_toggle: F) -> Self
+    where
+        F: FnMut(&DemandOption<T>, bool) + 'a,
+    {
+        self.on_toggle = Some(Box::new(on_toggle));
+        self
+    }
+
+    /// Set whether clearing the filter keeps the focused option focused instead of returning to
+    /// the first option
+    pub fn keep_focus_on_clear(mut self, keep_focus_on_clear: bool) -> Self {
+        self.keep_focus_on_clear = keep_focus_on_clear;
+        self
+    }
+
+    /// Set whether a min/max error is shown on its own line above the help keys
+    ///
+    /// By default the error is shown beside the help keys, where the filter would be.
+    pub fn error_on_own_line(mut self, error_on_own_line: bool) -> Self {
+        self.error_line = error_on_own_line;
+        self.fit_capacity();
+        self
+    }
+
+    /// Set whether filtering keeps matches in their original order instead of sorting them by
+    /// how well they match
+    pub fn stable_filter(mut self, stable_filter: bool) -> Self {
+        self.stable_filter = stable_filter;
+        self
+    }
+
+    /// Set whether selected options are listed before unselected ones, moving as they are toggled
+    ///
+    /// Within each group options keep the filter's order.
+    pub fn pin_selected(mut self, pin_selected: bool) -> Self {
+        self.pin_selected = pin_selected;
+        self
+    }
+
+    /// Set whether submitting with nothing selected selects the focused option first
+    ///
+    /// The selection is still checked against `min` and `max` before it is submitted.
+    pub fn enter_selects_focused(mut self, enter_selects_focused: bool) -> Self {
+        self.enter_selects_focused = enter_selects_focused;
+        self
+    }
+
+    /// Set whether a `↑ more` or `↓ more` line is shown when there are options on earlier or
+    /// later pages
+    pub fn scroll_indicators(mut self, scroll_indicators: bool) -> Self {
+        self.scroll_indicators = scroll_indicators;
+        self.fit_capacity();
+        self
+    }
+
+    /// Set the key that submits the selection. Defaults to `Key::Enter`.
+    ///
+    /// It takes precedence over the key's usual action, so setting it to `x` or space leaves
+    /// only the other as a toggle.
+    pub fn submit_key(mut self, key: Key) -> Self {
+        self.submit_key_name = help::key_name(&key);
+        self.submit_key = key;
+        self
+    }
+
+    /// Set whether the submit key has to be pressed twice in a row to submit
+    pub fn confirm_submit(mut self, confirm_submit: bool) -> Self {
+        self.confirm_submit = confirm_submit;
+        self
+    }
+
+    /// Set whether options are drawn without the checkbox prefix, for dense layouts
+    ///
+    /// The cursor takes a single column and selected options are told apart by the theme's
+    /// `selected_option` color. When colors are off or the theme does not distinguish selected
+    /// options, a one-character marker is kept instead.
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Set whether option descriptions are drawn beside the labels or on their own line below
+    pub fn description_layout(mut self, layout: DescriptionLayout) -> Self {
+        self.description_layout = layout;
+        self
+    }
+
+    /// Set whether the selector is drawn at a fixed position, so running it repeatedly does not
+    /// move down the screen
+    ///
+    /// The cursor position is saved when the selector starts, each redraw clears from there to the
+    /// end of the screen, and the cursor is returned there once it finishes. This is meant for
+    /// live dashboards; if drawing scrolls the terminal, the saved position no longer lines up.
+    pub fn fixed_position(mut self, fixed_position: bool) -> Self {
+        self.fixed_position = fixed_position;
+        self
+    }
+
+    /// Set a static line to display beneath the help keys
+    pub fn footer(mut self, footer: &str) -> Self {
+        let footer = footer.to_string();
+        self.footer = Some(Box::new(move || footer.clone()));
+        self
+    }
+
+    /// Set a status line to display beneath the help keys, recomputed each time it is drawn
+    pub fn status<F: Fn() -> String + 'a>(mut self, status: F) -> Self {
+        self.footer = Some(Box::new(status));
+        self
+    }
+
+    /// Set whether pressing `?` shows an overlay listing every keybinding
+    pub fn enable_help_overlay(mut self, help_overlay: bool) -> Self {
+        self.help_overlay = help_overlay;
+        self
+    }
+
+    /// Set whether to show an "esc back" hint, for flows that step back when the prompt is cancelled
+    pub fn back_hint(mut self, back_hint: bool) -> Self {
+        self.back_hint = back_hint;
+        self
+    }
+
+    /// Set whether to keep the terminal's cursor visible on the focused option instead of hiding
+    /// it, for screen readers and terminals that track the real cursor
+    pub fn show_system_cursor(mut self, show_system_cursor: bool) -> Self {
+        self.show_system_cursor = show_system_cursor;
+        self
+    }
+
+    /// Set whether vim-style navigation keys are bound
+    ///
+    /// When true, `g`/`G` jump to the first/last option and Ctrl+D/Ctrl+U move half a page down/up.
+    /// When false, `h`/`j`/`k`/`l` are unbound too, leaving only the arrow keys. By default only
+    /// `h`/`j`/`k`/`l` are bound.
+    pub fn vim_keys(mut self, vim_keys: bool) -> Self {
+        self.vim_keys = Some(vim_keys);
+        self
+    }
+
+    /// Set whether Escape clears the filter before cancelling (default: true)
+    ///
+    /// When false, Escape always cancels the prompt and Ctrl+U clears the filter instead.
+    pub fn escape_clears_filter(mut self, escape_clears_filter: bool) -> Self {
+        self.escape_clears_filter = escape_clears_filter;
+        self
+    }
+
+    /// Remember the chosen options in the JSON file at `path`, keyed by title, and select them
+    /// the next time the selector runs
+    ///
+    /// Failures to read or write the file are ignored.
+    pub fn remember(mut self, path: &Path) -> Self {
+        self.remember = Some(path.to_path_buf());
+        self
+    }
+
+    /// Select exactly the options at `positions`, as returned by `run_with_selection` or
+    /// `selection_descriptor`
+    ///
+    /// Positions count from 0 in the order the options were added; out of range positions are
+    /// ignored.
+    pub fn restore_selection(mut self, positions: &[usize]) -> Self {
+        for (i, option) in self.options.iter_mut().enumerate() {
+            option.selected = positions.contains(&i);
+            option.indeterminate = false;
+        }
+        self
+    }
+
+    /// The positions of the currently selected options, in the order they were added
+    pub fn selection_descriptor(&self) -> Vec<usize> {
+        self.options
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.selected)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Set the maximum width of the selector, wrapping longer lines
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.layout.max_width = Some(max_width);
+        self
+    }
+
+    /// Set whether the selector is centered horizontally in the terminal
+    pub fn center(mut self, center: bool) -> Self {
+        self.layout.center = center;
+        self
+    }
+
+    /// Cancel the selector with an `Interrupted` error once `token` is cancelled, e.g. from another
+    /// thread
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
     /// Set the theme of the selector
-    pub fn theme(mut self, theme: &'a Theme) -> Self {
-        self.theme = theme;
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Set how the line shown after submitting is formatted, replacing the default "Title value"
+    ///
+    /// Return an empty string to show nothing.
+    pub fn success_format(mut self, format: impl Fn(&Outcome) -> String + 'a) -> Self {
+        self.success_format = Some(Box::new(format));
+        self
+    }
+
+    /// Also write the line shown after submitting to `sink`, without colors
+    ///
+    /// Errors writing to `sink` are ignored.
+    pub fn tee(mut self, sink: Box<dyn Write + 'a>) -> Self {
+        self.tee = Some(sink);
         self
     }
 
+    /// The option currently under the cursor, or `None` if no options are shown
+    pub fn highlighted(&self) -> Option<&DemandOption<T>> {
+        self.visible_options().get(self.cursor).copied()
+    }
+
+    /// Whether `run` will draw the selector, rather than listing the options and reading
+    /// the selection from stdin because its terminal is not a TTY
+    pub fn is_interactive(&self) -> bool {
+        self.term.is_term()
+    }
+
+    /// Displays the selector like `run`, returning whether it was submitted, cancelled or failed
+    pub fn run_result(self) -> PromptResult<Vec<T>> {
+        self.run().into()
+    }
+
     /// Displays the selector to the user and returns their selected options
     ///
     /// This function will block until the user submits the input. If the user cancels the input,
     /// an error of type `io::ErrorKind::Interrupted` is returned.
-    pub fn run(mut self) -> io::Result<Vec<T>> {
-        let ctrlc_handle = ctrlc::show_cursor_after_ctrlc(&self.term)?;
+    ///
+    /// When stderr is not a terminal, the options are listed with numbers and a line is read
+    /// from stdin instead. It may contain comma-separated numbers and ranges such as `1-3,5`, or
+    /// `all` or `none`. An empty line keeps the initially selected options. Invalid input
+    /// returns an error of type `io::ErrorKind::InvalidInput`.
+    pub fn run(self) -> io::Result<Vec<T>> {
+        self.run_with_metrics().map(|(selected, _)| selected)
+    }
+
+    /// Displays the selector like `run`, also returning how the user interacted with it
+    pub fn run_with_metrics(self) -> io::Result<(Vec<T>, PromptMetrics)> {
+        self.run_all()
+            .map(|(selected, _, metrics)| (items(selected), metrics))
+    }
 
+    /// Displays the selector like `run`, also returning the positions of the selected options
+    ///
+    /// Passing the positions to `restore_selection` the next time the same options are shown
+    /// selects them again, without relying on their labels or items.
+    pub fn run_with_selection(self) -> io::Result<(Vec<T>, Vec<usize>)> {
+        self.run_all().map(|(selected, _, _)| {
+            let positions = selected.iter().map(|(i, _)| *i).collect();
+            (items(selected), positions)
+        })
+    }
+
+    /// Displays the selector to the user and returns their selected options along with the
+    /// options left partially selected
+    ///
+    /// Toggling an indeterminate option selects it, so only options the user never touched can
+    /// remain indeterminate.
+    pub fn run_with_indeterminate(self) -> io::Result<(Vec<T>, Vec<T>)> {
+        self.run_all()
+            .map(|(selected, indeterminate, _)| (items(selected), indeterminate))
+    }
+
+    /// Displays the selector like `run`, but returns the selected options in the order the user
+    /// selected them
+    ///
+    /// Options that were selected initially come first, in option order.
+    pub fn run_in_selection_order(mut self) -> io::Result<Vec<T>> {
+        self.in_selection_order = true;
+        self.run()
+    }
+
+    /// Displays the selector like `run`, but returns the selected options even when the user
+    /// cancels, along with whether they submitted (true) or cancelled (false)
+    ///
+    /// The selection limits are only checked on submit, so a cancelled selection may not meet
+    /// them.
+    pub fn run_with_cancel(mut self) -> io::Result<(Vec<T>, bool)> {
+        self.keep_on_cancel = true;
+        self.run_until_done()
+            .map(|((selected, _, _), submitted)| (items(selected), submitted))
+    }
+
+    /// Runs the selector as if its terminal were not a TTY, reading the selection from `input`
+    /// instead of stdin
+    ///
+    /// Returns the selected options and the listing that would have been written to the
+    /// terminal, so how piped input is parsed can be tested without a subprocess.
+    pub fn run_non_tty(mut self, input: &str) -> io::Result<(Vec<T>, String)> {
+        self.prepare();
+        let mut output = vec![];
+        let (selected, _, _) =
+            self.read_non_tty(Instant::now(), &mut input.as_bytes(), &mut output)?;
+        Ok((
+            items(selected),
+            String::from_utf8_lossy(&output).into_owned(),
+        ))
+    }
+
+    /// Applies the remembered selection and clamps the selection limits before running
+    fn prepare(&mut self) {
+        self.restore_remembered();
         self.max = self.max.min(self.options.len());
         self.min = self.min.min(self.max);
+        for (group, min, _) in &mut self.group_limits {
+            // disabled options can't be toggled, so only the selected ones count towards `min`
+            let selectable = self
+                .options
+                .iter()
+                .filter(|o| o.group.as_ref() == Some(group) && (!o.disabled || o.selected))
+                .count();
+            *min = (*min).min(selectable);
+        }
+    }
+
+    fn run_all(self) -> io::Result<Submitted<T>> {
+        self.run_until_done().map(|(submitted, _)| submitted)
+    }
+
+    /// Runs the selector, returning the selection and whether it was submitted rather than
+    /// cancelled with `keep_on_cancel` set
+    fn run_until_done(mut self) -> io::Result<(Submitted<T>, bool)> {
+        let started = Instant::now();
+        self.prepare();
+        if !self.term.is_term() {
+            let mut term = self.term.clone();
+            let submitted = self.read_non_tty(started, &mut io::stdin().lock(), &mut term)?;
+            return Ok((submitted, true));
+        }
+
+        let ctrlc_handle = ctrlc::show_cursor_after_ctrlc(&self.term)?;
+        if self.fixed_position {
+            term::save_cursor(&self.term)?;
+        }
+
+        self.fit_capacity();
+        self.pages = self.get_pages();
+        self.focus_enabled(true)?;
 
         loop {
             self.clear()?;
             let output = self.render()?;
+            let width = self.term.size().1 as usize;
+            let focused_row = self.focused_row.get();
+            // wrapping the lines above the focused option moves it down
+            self.focused_row
+                .set(focused_row.map(|row| self.layout.row(&output, row, width)));
+            let output = self.layout.apply(output, width);
             self.term.write_all(output.as_bytes())?;
             self.term.flush()?;
             self.height = output.lines().count() - 1;
             if self.filtering {
-                match self.term.read_key()? {
+                let key = self.read_key_at_focus(&output)?;
+                self.metrics.keystrokes += 1;
+                match key {
                     Key::ArrowLeft => self.handle_left()?,
                     Key::ArrowRight => self.handle_right()?,
                     Key::Enter => self.handle_stop_filtering(true)?,
-                    Key::Escape => self.handle_stop_filtering(false)?,
+                    Key::Escape if self.escape_clears_filter => {
+                        self.handle_stop_filtering(false)?
+                    }
+                    Key::Escape => {
+                        ctrlc_handle.close();
+                        return self.cancel_keeping(started);
+                    }
                     Key::Backspace => self.handle_filter_backspace()?,
+                    Key::Char(CTRL_U) => self.handle_filter_ctrl_u()?,
                     Key::Char(c) => self.handle_filter_key(c)?,
                     _ => {}
                 }
             } else {
-                self.term.hide_cursor()?;
-                match self.term.read_key()? {
-                    Key::ArrowDown | Key::Char('j') => self.handle_down()?,
-                    Key::ArrowUp | Key::Char('k') => self.handle_up()?,
-                    Key::ArrowLeft | Key::Char('h') => self.handle_left()?,
-                    Key::ArrowRight | Key::Char('l') => self.handle_right()?,
-                    Key::Char('x') | Key::Char(' ') => self.handle_toggle(),
-                    Key::Char('a') => self.handle_toggle_all(),
-                    Key::Char('/') if self.filterable => self.handle_start_filtering(),
-                    Key::Escape => {
-                        if self.filter.is_empty() {
-                            self.term.show_cursor()?;
-                            ctrlc_handle.close();
-                            return Err(io::Error::new(
-                                io::ErrorKind::Interrupted,
-                                "user cancelled",
-                            ));
+                if !self.show_system_cursor {
+                    self.term.hide_cursor()?;
+                }
+                let key = self.read_key_at_focus(&output)?;
+                self.metrics.keystrokes += 1;
+                if self.showing_help {
+                    self.showing_help = false;
+                    continue;
+                }
+                let pending_submit = std::mem::take(&mut self.pending_submit);
+                match key {
+                    _ if key == self.submit_key => {
+                        if self.confirm_submit && !pending_submit {
+                            self.pending_submit = true;
+                            continue;
                         }
-                        self.handle_stop_filtering(false)?
-                    }
-                    Key::Enter => {
+                        self.select_focused_if_empty();
+                        self.restore_options();
                         let selected = self
                             .options
                             .iter()
-                            .filter(|o| o.selected)
+                            .filter(|o| o.selected && self.is_shown(o))
                             .map(|o| o.label.to_string())
                             .collect::<Vec<_>>();
-                        if selected.len() < self.min {
-                            if self.min == 1 {
-                                self.err = Some("Please select an option".to_string());
-                            } else {
-                                self.err =
-                                    Some(format!("Please select at least {} options", self.min));
-                            }
-                            continue;
-                        }
-                        if selected.len() > self.max {
-                            if self.max == 1 {
-                                self.err = Some("Please select only one option".to_string());
-                            } else {
-                                self.err =
-                                    Some(format!("Please select at most {} options", self.max));
-                            }
+                        self.err = self.check_selection_count(selected.len()).or_else(|| {
+                            self.check_group_counts(
+                                self.options
+                                    .iter()
+                                    .filter(|o| o.selected && self.is_shown(o)),
+                            )
+                        });
+                        if self.err.is_some() {
                             continue;
                         }
                         self.clear()?;
                         self.term.show_cursor()?;
                         ctrlc_handle.close();
-                        let output = self.render_success(&selected)?;
-                        self.term.write_all(output.as_bytes())?;
-                        let selected = self
+                        let shown = self
                             .options
-                            .into_iter()
-                            .filter(|o| o.selected)
-                            .map(|o| o.item)
+                            .iter()
+                            .filter(|o| o.selected && self.is_shown(o))
+                            .map(|o| self.success_text(o))
                             .collect::<Vec<_>>();
-                        self.term.clear_to_end_of_screen()?;
-                        return Ok(selected);
+                        let output = self.render_success(&shown)?;
+                        let output = self.layout.apply(output, self.term.size().1 as usize);
+                        let metrics = PromptMetrics {
+                            elapsed: started.elapsed(),
+                            ..self.metrics.clone()
+                        };
+                        event::submitted(&self.observer, &self.title, &selected.join(", "));
+                        if let Some(path) = &self.remember {
+                            remember::save(path, &self.title, Remembered::Many(selected));
+                        }
+                        self.term.write_all(output.as_bytes())?;
+                        term::record_height(&self.term, &output);
+                        term::tee(&mut self.tee, &output);
+                        if !self.preserve_below {
+                            self.term.clear_to_end_of_screen()?;
+                        }
+                        if self.fixed_position {
+                            term::restore_cursor(&self.term)?;
+                        }
+                        return Ok((self.into_submitted(metrics), true));
                     }
-                    _ => {}
+                    Key::ArrowDown => self.handle_down()?,
+                    Key::ArrowUp => self.handle_up()?,
+                    Key::ArrowLeft => self.handle_left()?,
+                    Key::ArrowRight => self.handle_right()?,
+                    Key::Char('j') if self.vim_keys != Some(false) => self.handle_down()?,
+                    Key::Char('k') if self.vim_keys != Some(false) => self.handle_up()?,
+                    Key::Char('h') if self.vim_keys != Some(false) => self.handle_left()?,
+                    Key::Char('l') if self.vim_keys != Some(false) => self.handle_right()?,
+                    Key::Char('g') if self.vim_keys == Some(true) => self.handle_first()?,
+                    Key::Char('G') if self.vim_keys == Some(true) => self.handle_last()?,
+                    Key::Char(CTRL_D) if self.vim_keys == Some(true) => {
+                        self.handle_half_page(true)?
+                    }
+                    Key::Char(CTRL_U) if self.vim_keys == Some(true) => {
+                        self.handle_half_page(false)?
+                    }
+                    Key::Char('x') | Key::Char(' ') => self.handle_toggle(),
+                    Key::Char('a') => self.handle_toggle_all(),
+                    Key::Char('/') if self.filterable => self.handle_start_filtering(),
+                    Key::Char('?') if self.help_overlay => self.showing_help = true,
+                    Key::Escape if self.filter.is_empty() || !self.escape_clears_filter => {
+                        ctrlc_handle.close();
+                        return self.cancel_keeping(started);
+                    }
+                    Key::Escape => self.handle_stop_filtering(false)?,
+                    _ => self.handle_on_key(key),
+                }
+            }
+        }
+    }
+
+    fn read_non_tty(
+        mut self,
+        started: Instant,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> io::Result<Submitted<T>> {
+        let mut out = String::new();
+        out.push_str(&format!("{}\n", self.title));
+        if !self.description.is_empty() {
+            out.push_str(&format!("{}\n", self.description));
+        }
+        let (ids, labels): (Vec<_>, Vec<_>) = self
+            .filtered_options()
+            .iter()
+            .map(|o| (o.id, o.label.clone()))
+            .unzip();
+        let disabled = self
+            .filtered_options()
+            .iter()
+            .map(|o| o.disabled)
+            .collect::<Vec<_>>();
+        for (i, option) in self.filtered_options().iter().enumerate() {
+            out.push_str(&format!("  {}. {}\n", i + 1, option.label));
+        }
+        out.push_str("> ");
+        output.write_all(out.as_bytes())?;
+        output.flush()?;
+
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        if let Some(indices) = parse_selection(line.trim(), &labels, &disabled)? {
+            for option in self.options.iter_mut().filter(|o| !o.disabled) {
+                option.selected = indices.iter().any(|&i| ids[i] == option.id);
+            }
+        }
+
+        let count = self
+            .options
+            .iter()
+            .filter(|o| o.selected && ids.contains(&o.id))
+            .count();
+        let err = self.check_selection_count(count).or_else(|| {
+            self.check_group_counts(
+                self.options
+                    .iter()
+                    .filter(|o| o.selected && ids.contains(&o.id)),
+            )
+        });
+        if let Some(err) = err {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, err));
+        }
+        let (selected, rest): (Vec<_>, Vec<_>) = self
+            .options
+            .into_iter()
+            .enumerate()
+            .filter(|(_, o)| ids.contains(&o.id))
+            .partition(|(_, o)| o.selected);
+        let labels = selected.iter().map(|(_, o)| o.label.as_str()).join(", ");
+        event::submitted(&self.observer, &self.title, &labels);
+        if let Some(path) = &self.remember {
+            let labels = selected.iter().map(|(_, o)| o.label.clone()).collect();
+            remember::save(path, &self.title, Remembered::Many(labels));
+        }
+        let selected = selected.into_iter().map(|(i, o)| (i, o.item)).collect();
+        let indeterminate = rest
+            .into_iter()
+            .filter(|(_, o)| o.indeterminate)
+            .map(|(_, o)| o.item)
+            .collect();
+        let metrics = PromptMetrics {
+            elapsed: started.elapsed(),
+            ..Default::default()
+        };
+        Ok((selected, indeterminate, metrics))
+    }
+
+    /// Selects the remembered options, leaving the initial selection alone if none are remembered
+    fn restore_remembered(&mut self) {
+        let Some(path) = &self.remember else {
+            return;
+        };
+        let Some(Remembered::Many(labels)) = remember::load(path, &self.title) else {
+            return;
+        };
+        for option in &mut self.options {
+            option.selected = labels.contains(&option.label);
+        }
+    }
+
+    fn check_group_counts<'o>(
+        &self,
+        selected: impl Iterator<Item = &'o DemandOption<T>>,
+    ) -> Option<String>
+    where
+        T: 'o,
+    {
+        let selected = selected.collect::<Vec<_>>();
+        for (group, min, max) in &self.group_limits {
+            let count = selected
+                .iter()
+                .filter(|o| o.group.as_ref() == Some(group))
+                .count();
+            if count < *min {
+                if *min == 1 {
+                    return Some(format!("Please select an option from {group}"));
+                }
+                return Some(format!("Please select at least {min} options from {group}"));
+            }
+            if count > *max {
+                if *max == 1 {
+                    return Some(format!("Please select only one option from {group}"));
                 }
+                return Some(format!("Please select at most {max} options from {group}"));
             }
         }
+        None
+    }
+
+    fn check_selection_count(&self, count: usize) -> Option<String> {
+        if count < self.min {
+            if self.min == 1 {
+                return Some("Please select an option".to_string());
+            }
+            return Some(format!("Please select at least {} options", self.min));
+        }
+        if count > self.max {
+            if self.max == 1 {
+                return Some("Please select only one option".to_string());
+            }
+            return Some(format!("Please select at most {} options", self.max));
+        }
+        None
     }
 
     fn filtered_options(&self) -> Vec<&DemandOption<T>> {
+        self.ranked_options(usize::MAX)
+    }
+
+    /// The shown options matching the filter with their fuzzy match scores
+    fn matching_options(&self) -> impl Iterator<Item = (i64, &DemandOption<T>)> {
+        let filter =
+            (!self.filter.is_empty() && !self.filter_replaced).then(|| self.filter.to_lowercase());
         self.options
             .iter()
-            .filter_map(|opt| {
-                if self.filter.is_empty() {
-                    Some((0, opt))
-                } else {
-                    self.fuzzy_matcher
-                        .fuzzy_match(&opt.label.to_lowercase(), &self.filter.to_lowercase())
-                        .map(|score| (score, opt))
-                }
+            .filter(|opt| self.is_shown(opt))
+            .filter_map(move |opt| match &filter {
+                Some(filter) => self
+                    .fuzzy_matcher
+                    .fuzzy_match(&opt.label.to_lowercase(), filter)
+                    .map(|score| (score, opt)),
+                None => Some((0, opt)),
             })
-            .sorted_by_key(|(score, _opt)| -1 * *score)
-            .map(|(_score, opt)| opt)
-            .collect()
+    }
+
+    /// The first `n` matching options, pinned selections and then the best matches first
+    fn ranked_options(&self, n: usize) -> Vec<&DemandOption<T>> {
+        let matches = self.matching_options();
+        let sorted = !self.filter.is_empty() && !self.filter_replaced && !self.stable_filter;
+        if !sorted && !self.pin_selected {
+            return matches.map(|(_score, opt)| opt).take(n).collect();
+        }
+        let keyed = matches.map(|(score, opt)| {
+            let pinned = self.pin_selected && opt.selected;
+            ((!pinned, if sorted { -score } else { 0 }), opt)
+        });
+        rank::top(keyed, n)
+    }
+
+    fn is_shown(&self, option: &DemandOption<T>) -> bool {
+        match &self.option_filter {
+            Some(predicate) => predicate(option, &self.options),
+            None => true,
+        }
     }
 
     fn visible_options(&self) -> Vec<&DemandOption<T>> {
-        let filtered_options = self.filtered_options();
         let start = self.cur_page * self.capacity;
-        filtered_options
+        self.ranked_options(start + self.capacity)
             .into_iter()
             .skip(start)
-            .take(self.capacity)
             .collect()
     }
 
     fn handle_down(&mut self) -> Result<(), io::Error> {
-        let visible_options = self.visible_options();
-        if self.cursor < visible_options.len().max(1) - 1 {
+        self.metrics.navigations += 1;
+        let from = (self.cur_page, self.cursor);
+        if self.step_down()? {
+            self.focus_enabled(true)?;
+        }
+        if self.focused_disabled() {
+            (self.cur_page, self.cursor) = from;
+        }
+        Ok(())
+    }
+
+    fn handle_up(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        let from = (self.cur_page, self.cursor);
+        if self.step_up()? {
+            self.focus_enabled(false)?;
+        }
+        if self.focused_disabled() {
+            (self.cur_page, self.cursor) = from;
+        }
+        Ok(())
+    }
+
+    /// Moves the cursor to the next option, returning false when it is already on the last
+    fn step_down(&mut self) -> io::Result<bool> {
+        if self.cursor < self.visible_options().len().max(1) - 1 {
             self.cursor += 1;
         } else if self.pages > 0 && self.cur_page < self.pages - 1 {
             self.cur_page += 1;
             self.cursor = 0;
             self.term.clear_to_end_of_screen()?;
+        } else {
+            return Ok(false);
         }
-        Ok(())
+        Ok(true)
     }
 
-    fn handle_up(&mut self) -> Result<(), io::Error> {
+    /// Moves the cursor to the previous option, returning false when it is already on the first
+    fn step_up(&mut self) -> io::Result<bool> {
         if self.cursor > 0 {
             self.cursor -= 1;
         } else if self.cur_page > 0 {
             self.cur_page -= 1;
             self.cursor = self.visible_options().len().max(1) - 1;
             self.term.clear_to_end_of_screen()?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Moves the cursor down or up off disabled options, staying put if there are only disabled
+    /// options that way
+    fn focus_enabled(&mut self, down: bool) -> io::Result<()> {
+        let from = (self.cur_page, self.cursor);
+        while self.focused_disabled() {
+            let moved = match down {
+                true => self.step_down()?,
+                false => self.step_up()?,
+            };
+            if !moved {
+                (self.cur_page, self.cursor) = from;
+                break;
+            }
         }
         Ok(())
     }
 
+    fn focused_disabled(&self) -> bool {
+        self.highlighted().is_some_and(|o| o.disabled)
+    }
+
+    fn handle_first(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        if self.cur_page > 0 {
+            self.cur_page = 0;
+            self.term.clear_to_end_of_screen()?;
+        }
+        self.cursor = 0;
+        self.focus_enabled(true)
+    }
+
+    fn handle_last(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        let last_page = self.pages.saturating_sub(1);
+        if self.cur_page < last_page {
+            self.cur_page = last_page;
+            self.term.clear_to_end_of_screen()?;
+        }
+        self.cursor = self.visible_options().len().max(1) - 1;
+        self.focus_enabled(false)
+    }
+
+    /// Move half a page down or up, crossing onto the next or previous page as needed
+    fn handle_half_page(&mut self, down: bool) -> Result<(), io::Error> {
+        let navigations = self.metrics.navigations;
+        for _ in 0..(self.capacity / 2).max(1) {
+            match down {
+                true => self.handle_down()?,
+                false => self.handle_up()?,
+            }
+        }
+        self.metrics.navigations = navigations + 1;
+        Ok(())
+    }
+
     fn handle_left(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
         if self.filtering {
             if self.cursor_x > 0 {
                 self.cursor_x -= 1;
@@ -315,6 +1162,7 @@ impl<'a, T> MultiSelect<'a, T> {
     }
 
     fn handle_right(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
         if self.filtering {
             if self.cursor_x < self.filter.chars().count() {
                 self.cursor_x += 1;
@@ -335,18 +1183,46 @@ impl<'a, T> MultiSelect<'a, T> {
         if visible_options.is_empty() {
             return;
         }
+        if visible_options[self.cursor].disabled {
+            return;
+        }
         let id = visible_options[self.cursor].id;
-        let selected = visible_options[self.cursor].selected;
-        self.options
-            .iter_mut()
-            .find(|o| o.id == id)
-            .unwrap()
-            .selected = !selected;
+        let option = self.options.iter_mut().find(|o| o.id == id).unwrap();
+        option.selected = !option.selected || option.indeterminate;
+        option.indeterminate = false;
+        self.selection_order.retain(|&i| i != id);
+        if option.selected {
+            self.selection_order.push(id);
+        }
+        if let Some(on_toggle) = &mut self.on_toggle {
+            on_toggle(option, option.selected);
+        }
+        if self.option_filter.is_some() {
+            self.pages = self.get_pages();
+            self.cur_page = self.cur_page.min(self.pages.max(1) - 1);
+            self.cursor = self.cursor.min(self.visible_options().len().max(1) - 1);
+        }
+        if self.pin_selected {
+            self.focus_option(id);
+        }
+    }
+
+    /// Selects the focused option if `enter_selects_focused` is set and nothing is selected
+    fn select_focused_if_empty(&mut self) {
+        if self.enter_selects_focused
+            && !self.options.iter().any(|o| o.selected && self.is_shown(o))
+        {
+            self.handle_toggle();
+        }
     }
 
     fn handle_toggle_all(&mut self) {
         self.err = None;
-        let filtered_options = self.filtered_options();
+        let filtered_options = self
+            .filtered_options()
+            .into_iter()
+            .filter(|o| !o.disabled)
+            .collect::<Vec<_>>();
         if filtered_options.is_empty() {
             return;
         }
@@ -357,17 +1233,98 @@ impl<'a, T> MultiSelect<'a, T> {
             .collect::<HashSet<_>>();
         for opt in &mut self.options {
             if ids.contains(&opt.id) {
+                if select && !opt.selected {
+                    self.selection_order.push(opt.id);
+                } else if !select {
+                    self.selection_order.retain(|&i| i != opt.id);
+                }
+                let toggled = opt.selected != select;
                 opt.selected = select;
+                opt.indeterminate = false;
+                if let Some(on_toggle) = self.on_toggle.as_mut().filter(|_| toggled) {
+                    on_toggle(opt, select);
+                }
             }
         }
     }
 
     fn handle_start_filtering(&mut self) {
+        self.metrics.filtered = true;
         self.err = None;
         self.filtering = true;
     }
 
+    /// Cancels the prompt, as when Escape is pressed with no filter to clear
+    fn cancel<R>(&mut self) -> io::Result<R> {
+        self.leave_cancelled()?;
+        Err(io::Error::new(io::ErrorKind::Interrupted, "user cancelled"))
+    }
+
+    /// Cancels the prompt, or with `keep_on_cancel` set returns the current selection instead
+    fn cancel_keeping(mut self, started: Instant) -> io::Result<(Submitted<T>, bool)> {
+        if !self.keep_on_cancel {
+            return self.cancel();
+        }
+        self.leave_cancelled()?;
+        let metrics = PromptMetrics {
+            elapsed: started.elapsed(),
+            ..self.metrics.clone()
+        };
+        Ok((self.into_submitted(metrics), false))
+    }
+
+    fn leave_cancelled(&mut self) -> io::Result<()> {
+        if self.fixed_position {
+            self.clear()?;
+        }
+        self.term.show_cursor()?;
+        event::cancelled(&self.observer, &self.title);
+        Ok(())
+    }
+
+    /// Splits the shown options into the selected ones, with their positions, and the
+    /// indeterminate ones
+    fn into_submitted(mut self, metrics: PromptMetrics) -> Submitted<T> {
+        self.restore_options();
+        let shown = self
+            .options
+            .iter()
+            .filter(|o| self.is_shown(o))
+            .map(|o| o.id)
+            .collect::<HashSet<_>>();
+        let (mut selected, rest): (Vec<_>, Vec<_>) = self
+            .options
+            .into_iter()
+            .enumerate()
+            .filter(|(_, o)| shown.contains(&o.id))
+            .partition(|(_, o)| o.selected);
+        if self.in_selection_order {
+            selected.sort_by_key(|(_, o)| self.selection_order.iter().position(|&id| id == o.id));
+        }
+        let selected = selected.into_iter().map(|(i, o)| (i, o.item)).collect();
+        let indeterminate = rest
+            .into_iter()
+            .filter(|(_, o)| o.indeterminate)
+            .map(|(_, o)| o.item)
+            .collect();
+        (selected, indeterminate, metrics)
+    }
+
+    /// Reads the next key, with the cursor on the focused option while waiting if
+    /// `show_system_cursor` is set and the filter isn't being typed
+    fn read_key_at_focus(&mut self, output: &str) -> io::Result<Key> {
+        let focused = self.show_system_cursor && !self.filtering;
+        let Some(row) = self.focused_row.get().filter(|_| focused) else {
+            return cancel::read_key(&self.term, self.cancel_token.as_ref());
+        };
+        let lift = term::lift_cursor(&self.term, output, row)?;
+        let key = cancel::read_key(&self.term, self.cancel_token.as_ref());
+        self.term.move_cursor_down(lift)?;
+        key
+    }
+
     fn handle_stop_filtering(&mut self, save: bool) -> Result<(), io::Error> {
+        let focused = self.visible_options().get(self.cursor).map(|o| o.id);
         self.filtering = false;
 
         let visible_options = self.visible_options();
@@ -376,18 +1333,128 @@ impl<'a, T> MultiSelect<'a, T> {
         }
         if !save {
             self.filter.clear();
+            self.handle_filter_change();
             self.reset_paging();
+            if let Some(id) = focused.filter(|_| self.keep_focus_on_clear) {
+                self.focus_option(id);
+            }
         }
         self.term.clear_to_end_of_screen()
     }
 
+    fn handle_on_key(&mut self, key: Key) {
+        let Some(on_key) = &mut self.on_key else {
+            return;
+        };
+        if on_key(key, &mut self.options) == KeyOutcome::Rerender {
+            self.pages = self.get_pages();
+            self.cur_page = self.cur_page.min(self.pages.max(1) - 1);
+            self.cursor = self.cursor.min(self.visible_options().len().max(1) - 1);
+        }
+    }
+
+    fn focus_option(&mut self, id: usize) {
+        if let Some(idx) = self.filtered_options().iter().position(|o| o.id == id) {
+            self.cur_page = idx / self.capacity;
+            self.cursor = idx % self.capacity;
+        }
+    }
+
+    /// Passes the new filter to the `on_filter_change` hook, replacing the options it returns
+    fn handle_filter_change(&mut self) {
+        let Some(on_filter_change) = &mut self.on_filter_change else {
+            return;
+        };
+        let Some(mut options) = on_filter_change(&self.filter) else {
+            if self.filter.is_empty() {
+                self.restore_options();
+            }
+            return;
+        };
+        let replaced = std::mem::take(&mut self.options);
+        let original = match self.original_options.take() {
+            Some(mut original) => {
+                self.merge_selection(&mut original, replaced);
+                original
+            }
+            None => replaced,
+        };
+        // options the hook returns again stay selected
+        for option in &mut options {
+            option.selected |= original
+                .iter()
+                .any(|o| o.selected && o.label == option.label);
+        }
+        self.original_options = Some(original);
+        self.options = options;
+        self.filter_replaced = true;
+        if self.filter.is_empty() {
+            self.restore_options();
+        }
+    }
+
+    /// Puts back the user's own options replaced by `on_filter_change`, keeping the selection made
+    /// among the hook's options
+    fn restore_options(&mut self) {
+        self.filter_replaced = false;
+        let Some(mut original) = self.original_options.take() else {
+            return;
+        };
+        let replaced = std::mem::take(&mut self.options);
+        self.merge_selection(&mut original, replaced);
+        self.options = original;
+        self.pages = self.get_pages();
+    }
+
+    /// Copies the selection of `replaced`, options returned by `on_filter_change`, onto the
+    /// options with the same label in `original`, adding selected ones it doesn't have
+    fn merge_selection(
+        &mut self,
+        original: &mut Vec<DemandOption<T>>,
+        replaced: Vec<DemandOption<T>>,
+    ) {
+        for option in replaced {
+            match original.iter_mut().find(|o| o.label == option.label) {
+                Some(o) => {
+                    o.selected = option.selected;
+                    o.indeterminate = option.indeterminate;
+                    if self.selection_order.contains(&option.id) {
+                        self.selection_order.retain(|&id| id != o.id);
+                        for id in &mut self.selection_order {
+                            if *id == option.id {
+                                *id = o.id;
+                            }
+                        }
+                    }
+                }
+                None if option.selected => original.push(option),
+                None => {}
+            }
+        }
+    }
+
     fn handle_filter_key(&mut self, c: char) -> Result<(), io::Error> {
         let idx = self.get_char_idx(&self.filter, self.cursor_x);
         self.filter.insert(idx, c);
         self.cursor_x += 1;
+        self.handle_filter_change();
+        self.cursor_y = 0;
+        self.err = None;
+        self.reset_paging();
+        self.focus_enabled(true)?;
+        self.term.clear_to_end_of_screen()
+    }
+
+    /// Deletes the filter before the cursor, staying in filtering mode
+    fn handle_filter_ctrl_u(&mut self) -> Result<(), io::Error> {
+        let idx = self.get_char_idx(&self.filter, self.cursor_x);
+        self.filter.replace_range(..idx, "");
+        self.cursor_x = 0;
+        self.handle_filter_change();
         self.cursor_y = 0;
         self.err = None;
         self.reset_paging();
+        self.focus_enabled(true)?;
         self.term.clear_to_end_of_screen()
     }
 
@@ -400,26 +1467,47 @@ impl<'a, T> MultiSelect<'a, T> {
         if self.cursor_x > 0 {
             self.cursor_x -= 1;
         }
+        self.handle_filter_change();
         self.cursor_y = 0;
         self.err = None;
         self.reset_paging();
+        self.focus_enabled(true)?;
         self.term.clear_to_end_of_screen()
     }
 
+    /// The rows drawn besides the options, including the error line and scroll indicators
+    fn chrome_rows(&self) -> usize {
+        4 + self.description.lines().count().max(1)
+            + self.footer.is_some() as usize
+            + self.error_line as usize
+            + 2 * self.scroll_indicators as usize
+    }
+
+    fn fit_capacity(&mut self) {
+        self.capacity = term::fit_capacity(&self.term, self.chrome_rows());
+        if self.description_layout == DescriptionLayout::Below {
+            self.capacity = (self.capacity / option::rows_below(&self.options)).max(1);
+        }
+    }
+
     fn reset_paging(&mut self) {
         self.cur_page = 0;
         self.pages = self.get_pages();
     }
 
     fn get_pages(&self) -> usize {
-        if self.filtering || !self.filter.is_empty() {
-            ((self.filtered_options().len() as f64) / self.capacity as f64).ceil() as usize
+        if self.filtering || !self.filter.is_empty() || self.option_filter.is_some() {
+            ((self.matching_options().count() as f64) / self.capacity as f64).ceil() as usize
         } else {
             ((self.options.len() as f64) / self.capacity as f64).ceil() as usize
         }
     }
 
     fn render(&self) -> io::Result<String> {
+        self.focused_row.set(None);
+        if self.showing_help {
+            return help::render_overlay(&self.theme, &self.title, &self.help_overlay_keys());
+        }
         let mut out = Buffer::ansi();
 
         out.set_color(&self.theme.title)?;
@@ -439,10 +1527,22 @@ impl<'a, T> MultiSelect<'a, T> {
         let max_label_len = self
             .visible_options()
             .iter()
-            .map(|o| console::measure_text_width(&o.label))
+            .map(|o| console::measure_text_width(&o.label) + o.badge_width())
             .max()
             .unwrap_or(0);
+        if self.scroll_indicators && self.cur_page > 0 {
+            out.set_color(&self.theme.description)?;
+            writeln!(out, "  {} more", theme::glyph("↑", "^"))?;
+        }
         for (i, option) in self.visible_options().into_iter().enumerate() {
+            if self.cursor == i {
+                self.focused_row.set(Some(term::row(&out)));
+            }
+            if self.compact {
+                self.print_compact_prefix(&mut out, option, self.cursor == i)?;
+                self.print_option_label(&mut out, option, max_label_len)?;
+                continue;
+            }
             if self.cursor == i {
                 out.set_color(&self.theme.cursor)?;
                 write!(out, " >")?;
@@ -451,20 +1551,35 @@ impl<'a, T> MultiSelect<'a, T> {
             }
             if option.selected {
                 out.set_color(&self.theme.selected_prefix_fg)?;
-                write!(out, "{}", self.theme.selected_prefix)?;
+                write!(out, "{}", self.option_prefix(option))?;
                 out.set_color(&self.theme.selected_option)?;
-                self.print_option_label(&mut out, option, max_label_len)?;
+            } else if option.indeterminate {
+                out.set_color(&self.theme.indeterminate_prefix_fg)?;
+                write!(out, "{}", self.option_prefix(option))?;
+                out.set_color(&self.theme.unselected_option)?;
             } else {
                 out.set_color(&self.theme.unselected_prefix_fg)?;
-                write!(out, "{}", self.theme.unselected_prefix)?;
+                write!(out, "{}", self.option_prefix(option))?;
                 out.set_color(&self.theme.unselected_option)?;
-                self.print_option_label(&mut out, option, max_label_len)?;
             }
+            self.print_option_label(&mut out, option, max_label_len)?;
+        }
+        if self.scroll_indicators && self.cur_page + 1 < self.pages {
+            out.set_color(&self.theme.description)?;
+            writeln!(out, "  {} more", theme::glyph("↓", "v"))?;
         }
         if self.pages > 1 {
             out.set_color(&self.theme.description)?;
             writeln!(out, " (page {}/{})", self.cur_page + 1, self.pages)?;
         }
+        if let Some(err) = self.err.as_ref().filter(|_| self.error_line) {
+            out.set_color(&self.theme.error_indicator)?;
+            writeln!(out, "{} {}", theme::glyph("✗", "x"), err)?;
+        }
+        if self.pending_submit {
+            out.set_color(&self.theme.description)?;
+            writeln!(out, "Press {} again to confirm", self.submit_key_name)?;
+        }
 
         if self.filtering {
             out.set_color(&self.theme.input_cursor)?;
@@ -494,17 +1609,70 @@ impl<'a, T> MultiSelect<'a, T> {
         } else if !self.filter.is_empty() {
             out.set_color(&self.theme.description)?;
             write!(out, "/{}", self.filter)?;
-        } else if let Some(err) = &self.err {
+        } else if let Some(err) = self.err.as_ref().filter(|_| !self.error_line) {
             out.set_color(&self.theme.error_indicator)?;
             write!(out, " {}", err)?;
         }
 
         self.print_help_keys(&mut out)?;
 
-        writeln!(out)?;
-        out.reset()?;
+        writeln!(out)?;
+        out.reset()?;
+        help::render_footer(&mut out, &self.theme, &self.footer)?;
+
+        Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
+    }
+
+    fn option_prefix(&self, option: &DemandOption<T>) -> &str {
+        if option.selected {
+            self.selected_prefix
+                .as_deref()
+                .unwrap_or(&self.theme.selected_prefix)
+        } else if option.indeterminate {
+            &self.theme.indeterminate_prefix
+        } else {
+            self.unselected_prefix
+                .as_deref()
+                .unwrap_or(&self.theme.unselected_prefix)
+        }
+    }
+
+    /// Writes the cursor column and, if color alone cannot show the selection, a marker
+    fn print_compact_prefix(
+        &self,
+        out: &mut Buffer,
+        option: &DemandOption<T>,
+        focused: bool,
+    ) -> io::Result<()> {
+        out.set_color(&self.theme.cursor)?;
+        write!(out, "{}", if focused { ">" } else { " " })?;
+        if self.compact_marker() {
+            let marker = match option {
+                o if o.selected => theme::glyph("•", "*"),
+                o if o.indeterminate => "-",
+                _ => " ",
+            };
+            out.set_color(&self.theme.selected_prefix_fg)?;
+            write!(out, "{marker}")?;
+        }
+        match option.selected {
+            true => out.set_color(&self.theme.selected_option),
+            false => out.set_color(&self.theme.unselected_option),
+        }
+    }
+
+    /// Whether compact rows need a marker because selected options look like unselected ones
+    fn compact_marker(&self) -> bool {
+        !console::colors_enabled_stderr()
+            || self.theme.selected_option == self.theme.unselected_option
+    }
 
-        Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
+    /// The number of columns drawn before an option's label
+    fn prefix_width(&self, option: &DemandOption<T>) -> usize {
+        match self.compact {
+            true => 1 + self.compact_marker() as usize,
+            false => 2 + console::measure_text_width(self.option_prefix(option)),
+        }
     }
 
     fn print_option_label(
@@ -513,55 +1681,116 @@ impl<'a, T> MultiSelect<'a, T> {
         option: &DemandOption<T>,
         max_label_len: usize,
     ) -> io::Result<()> {
+        let label_color = match option.selected {
+            _ if option.disabled => &self.theme.description,
+            true => &self.theme.selected_option,
+            false => &self.theme.unselected_option,
+        };
+        out.set_color(label_color)?;
         if let Some(desc) = &option.description {
-            let label = console::pad_str(&option.label, max_label_len, Alignment::Left, None);
             if self.filtering && !self.filter.is_empty() {
-                self.highlight_matches(out, &label)?;
+                self.highlight_matches(out, &option.label)?;
             } else {
+                let label = term::hyperlink(&option.label, option.link.as_deref());
                 write!(out, " {}", label)?;
             }
-            out.set_color(&self.theme.description)?;
-            writeln!(out, "  {}", desc)?;
+            option::write_badge(out, option, &self.theme, label_color)?;
+            if self.description_layout == DescriptionLayout::Below {
+                out.set_color(&self.theme.description)?;
+                option::write_description_below(out, desc, self.prefix_width(option) + 1)?;
+            } else {
+                let width = console::measure_text_width(&option.label) + option.badge_width();
+                write!(out, "{}", " ".repeat(max_label_len - width))?;
+                out.set_color(&self.theme.description)?;
+                let indent = self.prefix_width(option) + 1 + max_label_len;
+                option::write_description(out, desc, indent)?;
+            }
         } else if self.filtering && !self.filter.is_empty() {
             self.highlight_matches(out, &option.label)?;
+            option::write_badge(out, option, &self.theme, label_color)?;
             writeln!(out)?;
         } else {
-            writeln!(out, " {}", option.label)?;
+            let label = term::hyperlink(&option.label, option.link.as_deref());
+            write!(out, " {}", label)?;
+            option::write_badge(out, option, &self.theme, label_color)?;
+            writeln!(out)?;
         }
         Ok(())
     }
 
+    /// The key shown for clearing the filter while filtering
+    fn clear_filter_key(&self) -> &'static str {
+        match self.escape_clears_filter {
+            true => "esc",
+            false => "ctrl+u",
+        }
+    }
+
     fn print_help_keys(&self, out: &mut Buffer) -> io::Result<()> {
-        let mut help_keys = vec![("↑/↓/k/j", "up/down")];
+        let [up_down, prev_next] = select::nav_keys(self.vim_keys);
+        let mut help_keys = vec![(up_down, "up/down")];
         if self.pages > 1 {
-            help_keys.push(("←/→/h/l", "prev/next page"));
+            help_keys.push((prev_next, "prev/next page"));
         }
         help_keys.push(("x/space", "toggle"));
         help_keys.push(("a", "toggle all"));
         if self.filterable {
             if self.filtering {
-                help_keys = vec![("esc", "clear filter"), ("enter", "save filter")];
+                help_keys = vec![
+                    (self.clear_filter_key(), "clear filter"),
+                    ("enter", "save filter"),
+                ];
             } else {
                 help_keys.push(("/", "filter"));
-                if !self.filter.is_empty() {
+                if !self.filter.is_empty() && self.escape_clears_filter {
                     help_keys.push(("esc", "clear filter"));
                 }
             }
         }
         if !self.filtering {
-            help_keys.push(("enter", "confirm"));
-        }
-        for (i, (key, desc)) in help_keys.iter().enumerate() {
-            if i > 0 || (!self.filtering && !self.filter.is_empty()) {
-                out.set_color(&self.theme.help_sep)?;
-                write!(out, " • ")?;
+            if self.help_overlay {
+                help_keys.push(("?", "help"));
+            }
+            help_keys.push((&self.submit_key_name, "confirm"));
+            if self.back_hint && self.filter.is_empty() {
+                help_keys.push(("esc", "back"));
             }
-            out.set_color(&self.theme.help_key)?;
-            write!(out, "{}", key)?;
-            out.set_color(&self.theme.help_desc)?;
-            write!(out, " {}", desc)?;
         }
-        Ok(())
+        let start_col = match &self.err {
+            _ if self.filtering => 0,
+            _ if !self.filter.is_empty() => 1 + console::measure_text_width(&self.filter),
+            Some(err) if !self.error_line => 1 + console::measure_text_width(err),
+            Some(_) => 0,
+            None => 0,
+        };
+        help::render_keys(
+            out,
+            &self.theme,
+            &help_keys,
+            !self.filtering && !self.filter.is_empty(),
+            start_col,
+            self.term.size().1 as usize,
+        )
+    }
+
+    fn help_overlay_keys(&self) -> Vec<(&str, &'static str)> {
+        let [up_down, prev_next] = select::nav_keys(self.vim_keys);
+        let mut keys = vec![(up_down, "up/down"), (prev_next, "prev/next page")];
+        if self.vim_keys == Some(true) {
+            keys.extend(select::VIM_KEYS);
+        }
+        keys.extend([("x/space", "toggle"), ("a", "toggle all")]);
+        if self.filterable {
+            keys.push(("/", "filter"));
+            keys.push(("ctrl+u", "clear filter while typing"));
+        }
+        if self.filterable && self.escape_clears_filter {
+            keys.push(("esc", "clear filter or cancel"));
+        } else {
+            keys.push(("esc", "cancel"));
+        }
+        keys.push((&self.submit_key_name, "confirm"));
+        keys
     }
 
     fn get_char_idx(&self, input: &str, cursor: usize) -> usize {
@@ -598,7 +1827,22 @@ impl<'a, T> MultiSelect<'a, T> {
         Ok(())
     }
 
+    /// The text shown for `option` on the line shown after submitting
+    fn success_text(&self, option: &DemandOption<T>) -> String {
+        match self.success_item {
+            Some(text) => text(&option.item),
+            None => option.label.clone(),
+        }
+    }
+
     fn render_success(&self, selected: &[String]) -> io::Result<String> {
+        if let Some(format) = &self.success_format {
+            let outcome = Outcome {
+                title: &self.title,
+                values: selected.iter().map(String::as_str).collect(),
+            };
+            return Ok(outcome::render(format, &outcome));
+        }
         let mut out = Buffer::ansi();
         out.set_color(&self.theme.title)?;
         write!(out, "{}", self.title)?;
@@ -609,12 +1853,78 @@ impl<'a, T> MultiSelect<'a, T> {
     }
 
     fn clear(&mut self) -> io::Result<()> {
-        self.term.clear_last_lines(self.height)?;
+        if self.fixed_position {
+            term::restore_cursor(&self.term)?;
+            self.term.clear_to_end_of_screen()?;
+        } else {
+            self.term.clear_last_lines(self.height)?;
+        }
         self.height = 0;
         Ok(())
     }
 }
 
+/// Parses a non-interactive selection such as `1-3,5` into 0-based indices
+///
+/// Returns `None` for empty input so the initial selection is kept. `all` leaves out the options
+/// marked in `disabled`, while naming one, alone or in a range, is an error.
+fn parse_selection<S: AsRef<str>>(
+    input: &str,
+    labels: &[S],
+    disabled: &[bool],
+) -> io::Result<Option<Vec<usize>>> {
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let mut indices = Vec::new();
+    for token in input.split(',').map(str::trim) {
+        match token.to_lowercase().as_str() {
+            "all" => indices.extend((0..labels.len()).filter(|&i| !disabled[i])),
+            "none" => {}
+            _ => match token.split_once('-').filter(|(start, end)| {
+                start.trim().parse::<usize>().is_ok() && end.trim().parse::<usize>().is_ok()
+            }) {
+                Some((start, end)) => {
+                    let start = option::find_option(start.trim(), labels)?;
+                    let end = option::find_option(end.trim(), labels)?;
+                    if start > end {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("invalid selection: {token}"),
+                        ));
+                    }
+                    indices.extend(start..=end);
+                }
+                None => indices.push(option::find_option(token, labels)?),
+            },
+        }
+    }
+    if let Some(&i) = indices.iter().find(|&&i| disabled[i]) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("option is disabled: {}", labels[i].as_ref()),
+        ));
+    }
+    Ok(Some(indices))
+}
+
+/// Drop the positions `run_all` returns alongside the selected items
+fn items<T>(selected: Vec<(usize, T)>) -> Vec<T> {
+    selected.into_iter().map(|(_, item)| item).collect()
+}
+
+impl<T: Display> MultiSelect<'_, T> {
+    /// Set whether the line shown after submitting displays the option labels (the default) or
+    /// the items themselves
+    pub fn success_shows(mut self, shows: SuccessShows) -> Self {
+        self.success_item = match shows {
+            SuccessShows::Label => None,
+            SuccessShows::Item => Some(T::to_string),
+        };
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::without_ansi;
@@ -652,6 +1962,221 @@ mod tests {
         );
     }
 
+    #[test]
+    fn error_on_own_line() {
+        let mut select = MultiSelect::new("Toppings")
+            .min(1)
+            .error_on_own_line(true)
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Cheese"));
+        select.err = select.check_selection_count(0);
+
+        assert_eq!(
+            indoc! {
+              "Toppings *
+             >[ ] Lettuce
+              [ ] Cheese
+            ✗ Please select an option
+            ↑/↓/k/j up/down • x/space toggle • a toggle all • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn submit_key() {
+        let mut select = MultiSelect::new("Toppings")
+            .submit_key(Key::Char('\u{13}'))
+            .confirm_submit(true)
+            .option(DemandOption::new("Lettuce"));
+        select.pending_submit = true;
+
+        assert_eq!(
+            indoc! {
+              "Toppings
+             >[ ] Lettuce
+            Press ctrl+s again to confirm
+            ↑/↓/k/j up/down • x/space toggle • a toggle all • ctrl+s confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn group_limits() {
+        let select = MultiSelect::new("Order")
+            .group_limits("Size", 1, 1)
+            .group_limits("Toppings", 0, 2)
+            .option(DemandOption::new("Small").group("Size").selected(true))
+            .option(DemandOption::new("Large").group("Size").selected(true))
+            .option(DemandOption::new("Cheese").group("Toppings"));
+        let selected = || select.options.iter().filter(|o| o.selected);
+
+        assert_eq!(
+            Some("Please select only one option from Size".to_string()),
+            select.check_group_counts(selected())
+        );
+        assert_eq!(None, select.check_group_counts(selected().skip(1)));
+        assert_eq!(
+            Some("Please select an option from Size".to_string()),
+            select.check_group_counts(selected().skip(2))
+        );
+    }
+
+    #[test]
+    fn group_limits_without_selectable_options() {
+        let select = MultiSelect::new("Order")
+            .group_limits("Size", 1, 1)
+            .group_limits("Sauce", 1, 2)
+            .group_limits("Extras", 2, 2)
+            .option(DemandOption::new("Small").group("Size").disabled(true))
+            .option(DemandOption::new("Ketchup").group("Extras"))
+            .option(DemandOption::new("Mayo").group("Extras").disabled(true));
+        let (items, _) = select.run_non_tty("2\n").unwrap();
+        assert_eq!(vec!["Ketchup"], items);
+    }
+
+    #[test]
+    fn selection_order() {
+        let mut select = MultiSelect::new("Playlist")
+            .option(DemandOption::new("First"))
+            .option(DemandOption::new("Second"))
+            .option(DemandOption::new("Third"));
+        select.capacity = 3;
+        let ids = select.options.iter().map(|o| o.id).collect::<Vec<_>>();
+
+        select.cursor = 2;
+        select.handle_toggle();
+        select.cursor = 0;
+        select.handle_toggle();
+        assert_eq!(vec![ids[2], ids[0]], select.selection_order);
+        select.handle_toggle_all();
+        assert_eq!(vec![ids[2], ids[0], ids[1]], select.selection_order);
+        select.cursor = 0;
+        select.handle_toggle();
+        assert_eq!(vec![ids[2], ids[1]], select.selection_order);
+    }
+
+    #[test]
+    fn prefix_overrides() {
+        let select = MultiSelect::new("Toppings")
+            .selected_prefix("(•)")
+            .unselected_prefix("( )")
+            .option(DemandOption::new("Lettuce").selected(true))
+            .option(DemandOption::new("Cheese"));
+
+        assert_eq!(
+            indoc! {
+              "Toppings
+             >(•) Lettuce
+              ( ) Cheese
+            ↑/↓/k/j up/down • x/space toggle • a toggle all • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn indeterminate() {
+        let mut select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Sauces").indeterminate(true))
+            .option(DemandOption::new("Cheese"));
+
+        assert_eq!(
+            indoc! {
+              "Toppings
+             >[-] Sauces
+              [ ] Cheese
+            ↑/↓/k/j up/down • x/space toggle • a toggle all • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+
+        select.handle_toggle();
+        assert!(select.options[0].selected);
+        assert!(!select.options[0].indeterminate);
+        select.handle_toggle();
+        assert!(!select.options[0].selected);
+    }
+
+    #[test]
+    fn filter_options() {
+        let mut select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Cheese").selected(true))
+            .option(DemandOption::new("Vegan Cheese"))
+            .filter_options(|option, options| {
+                option.label != "Vegan Cheese" || !options.iter().any(|o| o.selected)
+            });
+
+        assert_eq!(
+            indoc! {
+              "Toppings
+             >[•] Cheese
+            ↑/↓/k/j up/down • x/space toggle • a toggle all • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+
+        select.handle_toggle();
+        assert_eq!(2, select.visible_options().len());
+    }
+
+    #[test]
+    fn selection_input() {
+        let labels = ["Lettuce", "Tomatoes", "Charm Sauce", "Jalapeños", "Cheese"];
+        let parse_selection =
+            |input, labels: &[&str]| parse_selection(input, labels, &vec![false; labels.len()]);
+        assert_eq!(None, parse_selection("", &labels).unwrap());
+        assert_eq!(
+            Some(vec![0, 1, 2, 4]),
+            parse_selection("1-3, 5", &labels).unwrap()
+        );
+        assert_eq!(
+            Some(vec![0, 1, 2]),
+            parse_selection("all", &labels[..3]).unwrap()
+        );
+        assert_eq!(Some(vec![]), parse_selection("None", &labels[..3]).unwrap());
+        assert_eq!(
+            Some(vec![1, 3]),
+            parse_selection("tomatoes, jal", &labels).unwrap()
+        );
+
+        let err = parse_selection("1,x", &labels[..3]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!("invalid selection: x", err.to_string());
+        assert!(parse_selection("2-4", &labels[..3]).is_err());
+        assert!(parse_selection("3-1", &labels[..3]).is_err());
+        let err = parse_selection("ch", &labels).unwrap_err();
+        assert_eq!(
+            "ambiguous selection: ch matches Charm Sauce, Cheese",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn multiline_description() {
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Cheese").description("Aged\nSharp"))
+            .option(DemandOption::new("Ham"));
+
+        assert_eq!(
+            indoc! {
+              "Toppings
+             >[ ] Cheese  Aged
+                          Sharp
+              [ ] Ham
+            ↑/↓/k/j up/down • x/space toggle • a toggle all • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
     #[test]
     fn non_display() {
         struct Thing {
@@ -700,4 +2225,294 @@ mod tests {
             without_ansi(select.render().unwrap().as_str())
         );
     }
+
+    #[test]
+    fn success_format() {
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .success_format(|o| format!("{} ({})", o.title, o.values.len()));
+        let selected = vec!["Lettuce".to_string(), "Cheese".to_string()];
+        assert_eq!(
+            "Toppings (2)\n",
+            without_ansi(select.render_success(&selected).unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn success_shows() {
+        let select = MultiSelect::new("Ports").option(DemandOption::with_label("HTTPS", 443));
+        assert_eq!("HTTPS", select.success_text(&select.options[0]));
+        let select = select.success_shows(SuccessShows::Item);
+        assert_eq!("443", select.success_text(&select.options[0]));
+    }
+
+    #[test]
+    fn compact() {
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce").selected(true))
+            .option(DemandOption::new("Tomatoes"))
+            .compact(true);
+        assert_eq!(
+            indoc! {
+              "Toppings
+            >• Lettuce
+               Tomatoes
+            ↑/↓/k/j up/down • x/space toggle • a toggle all • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn start_filtering_with() {
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"))
+            .option(DemandOption::new("Cheese"))
+            .start_filtering_with("to");
+        assert_eq!(2, select.cursor_x);
+        assert_eq!(
+            indoc! {
+              "Toppings
+             >[ ] Tomatoes
+            /to 
+            esc clear filter • enter save filter
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn pin_selected() {
+        let mut select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"))
+            .option(DemandOption::new("Cheese").selected(true))
+            .pin_selected(true);
+        assert_eq!(
+            vec!["Cheese", "Lettuce", "Tomatoes"],
+            select
+                .filtered_options()
+                .iter()
+                .map(|o| o.label.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        select.cursor = 2;
+        select.handle_toggle();
+        assert_eq!(
+            vec!["Tomatoes", "Cheese", "Lettuce"],
+            select
+                .filtered_options()
+                .iter()
+                .map(|o| o.label.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(0, select.cursor);
+    }
+
+    #[test]
+    fn on_toggle() {
+        let toggled = std::cell::RefCell::new(vec![]);
+        let mut select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes").selected(true))
+            .on_toggle(|o, selected| toggled.borrow_mut().push((o.label.clone(), selected)));
+        select.handle_toggle();
+        select.handle_toggle_all();
+        drop(select);
+        assert_eq!(
+            vec![
+                ("Lettuce".to_string(), true),
+                ("Lettuce".to_string(), false),
+                ("Tomatoes".to_string(), false),
+            ],
+            toggled.into_inner()
+        );
+    }
+
+    #[test]
+    fn restore_selection() {
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce").selected(true))
+            .option(DemandOption::new("Tomatoes"))
+            .option(DemandOption::new("Cheese"))
+            .restore_selection(&[1, 2, 5]);
+        assert_eq!(vec![1, 2], select.selection_descriptor());
+    }
+
+    #[test]
+    fn enter_selects_focused() {
+        let mut select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"))
+            .enter_selects_focused(true);
+        select.cursor = 1;
+        select.select_focused_if_empty();
+        assert_eq!(vec![1], select.selection_descriptor());
+        select.cursor = 0;
+        select.select_focused_if_empty();
+        assert_eq!(vec![1], select.selection_descriptor());
+    }
+
+    #[test]
+    fn run_non_tty() {
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"))
+            .option(DemandOption::new("Cheese"))
+            .max(2);
+        let (items, _) = select.run_non_tty("1,3\n").unwrap();
+        assert_eq!(vec!["Lettuce", "Cheese"], items);
+
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"))
+            .max(1);
+        let err = select.run_non_tty("all\n").unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn disabled() {
+        let mut select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes").disabled(true))
+            .option(DemandOption::new("Cheese").disabled(true).selected(true));
+        select.handle_down().unwrap();
+        assert_eq!("Lettuce", select.highlighted().unwrap().label);
+        select.handle_toggle_all();
+        let selected = select
+            .options
+            .iter()
+            .map(|o| o.selected)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![true, false, true], selected);
+        select.cursor = 1;
+        select.handle_toggle();
+        assert!(!select.options[1].selected);
+    }
+
+    #[test]
+    fn disabled_non_tty() {
+        let toppings = || {
+            MultiSelect::new("Toppings")
+                .option(DemandOption::new("Lettuce"))
+                .option(DemandOption::new("Tomatoes").disabled(true))
+                .option(DemandOption::new("Cheese"))
+        };
+        let err = toppings().run_non_tty("1,2\n").unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!("option is disabled: Tomatoes", err.to_string());
+        let (items, _) = toppings().run_non_tty("all\n").unwrap();
+        assert_eq!(vec!["Lettuce", "Cheese"], items);
+    }
+
+    #[test]
+    fn cancel_keeping() {
+        let toppings = || {
+            MultiSelect::new("Toppings")
+                .option(DemandOption::new("Lettuce"))
+                .option(DemandOption::new("Tomatoes"))
+        };
+        let mut select = toppings();
+        select.cursor = 1;
+        select.handle_toggle();
+        select.keep_on_cancel = true;
+        let ((selected, _, _), submitted) = select.cancel_keeping(Instant::now()).unwrap();
+        assert_eq!(vec![(1, "Tomatoes")], selected);
+        assert!(!submitted);
+
+        let err = toppings().cancel_keeping(Instant::now()).unwrap_err();
+        assert_eq!(io::ErrorKind::Interrupted, err.kind());
+    }
+
+    #[test]
+    fn on_filter_change_keeps_selection() {
+        let mut select = MultiSelect::new("Toppings")
+            .filterable(true)
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"))
+            .on_filter_change(|query| {
+                (!query.is_empty())
+                    .then(|| vec![DemandOption::new("Lettuce"), DemandOption::new("Cheese")])
+            });
+        select.handle_toggle();
+        select.handle_start_filtering();
+        select.handle_filter_key('x').unwrap();
+        assert!(select.filter_replaced);
+        select.handle_stop_filtering(false).unwrap();
+        assert!(!select.filter_replaced);
+
+        let (selected, _, _) = select.into_submitted(PromptMetrics::default());
+        assert_eq!(vec![(0, "Lettuce")], selected);
+    }
+
+    #[test]
+    fn on_filter_change_keeps_unreturned_selection() {
+        let mut select = MultiSelect::new("Packages")
+            .filterable(true)
+            .option(DemandOption::new("serde".to_string()))
+            .option(DemandOption::new("tokio".to_string()))
+            .on_filter_change(|query| Some(vec![DemandOption::new(format!("{query}-cli"))]));
+        select.handle_toggle();
+        select.handle_start_filtering();
+        select.handle_filter_key('x').unwrap();
+        assert_eq!("x-cli", select.options[0].label);
+        select.handle_toggle();
+        select.handle_filter_key('y').unwrap();
+        assert_eq!(1, select.options.len());
+        assert_eq!("xy-cli", select.options[0].label);
+
+        // submitting while the hook's results are shown still returns the earlier picks
+        let (selected, _, _) = select.into_submitted(PromptMetrics::default());
+        assert_eq!(
+            vec![(0, "serde".to_string()), (2, "x-cli".to_string())],
+            selected
+        );
+    }
+
+    #[test]
+    fn filter_skips_disabled() {
+        let mut select = MultiSelect::new("Fruit")
+            .filterable(true)
+            .option(DemandOption::new("Apple").disabled(true))
+            .option(DemandOption::new("Apricot"));
+        select.handle_start_filtering();
+        select.handle_filter_key('a').unwrap();
+        assert_eq!(1, select.cursor);
+    }
+
+    #[test]
+    fn observe() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let select = MultiSelect::new("Toppings")
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"))
+            .option(DemandOption::new("Cheese"))
+            .observe(tx);
+        select.run_non_tty("1,3\n").unwrap();
+        let event = PromptEvent::Submitted {
+            title: "Toppings".to_string(),
+            value: "Lettuce, Cheese".to_string(),
+        };
+        assert_eq!(vec![event], rx.try_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn metrics() {
+        let mut select = MultiSelect::new("Toppings")
+            .filterable(true)
+            .option(DemandOption::new("Lettuce"))
+            .option(DemandOption::new("Tomatoes"));
+        select.handle_down().unwrap();
+        select.handle_start_filtering();
+        select.keep_on_cancel = true;
+
+        let ((_, _, metrics), _) = select.cancel_keeping(Instant::now()).unwrap();
+        assert_eq!(1, metrics.navigations);
+        assert!(metrics.filtered);
+    }
 }
@@ -1,31 +1,70 @@
 use std::fmt::Display;
+use std::io;
+use std::io::Write;
 use std::sync::atomic::AtomicUsize;
 
+use termcolor::{ColorSpec, WriteColor};
+
+use crate::Theme;
+
+static ID: AtomicUsize = AtomicUsize::new(0);
+
+fn next_id() -> usize {
+    ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+}
+
 /// An individual option in a select or multi-select.
 #[derive(Debug, Clone)]
 pub struct DemandOption<T> {
     /// Unique ID for this option.
     pub(crate) id: usize,
+    /// Position this option was added at in its select, which sorting leaves unchanged.
+    pub(crate) index: usize,
     /// The item this option represents.
     pub item: T,
     /// Display label for this option.
     pub label: String,
     /// Whether this option is initially selected.
     pub selected: bool,
+    /// Whether this option is initially partially selected, e.g. a parent whose children are
+    /// mixed. Ignored when `selected` is true.
+    pub indeterminate: bool,
     /// Optional description shown on the side.
     pub description: Option<String>,
+    /// Optional shortcut key that focuses this option in a select.
+    pub key: Option<char>,
+    /// Optional link target the label is rendered as a terminal hyperlink to.
+    pub link: Option<String>,
+    /// Optional name of the group this option belongs to.
+    pub group: Option<String>,
+    /// Optional divider row shown above this option in a select.
+    pub separator: Option<String>,
+    /// Optional short tag such as "beta" shown after the label.
+    pub badge: Option<String>,
+    /// Color of the badge, overriding the theme's badge color.
+    pub badge_color: Option<ColorSpec>,
+    /// Whether this option is shown dimmed and can't be focused or chosen.
+    pub disabled: bool,
 }
 
 impl<T: ToString> DemandOption<T> {
     /// Create a new option with the item as the label
     pub fn new(item: T) -> Self {
-        static ID: AtomicUsize = AtomicUsize::new(0);
         Self {
-            id: ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            id: next_id(),
+            index: 0,
             label: item.to_string(),
             item,
             selected: false,
+            indeterminate: false,
             description: None,
+            key: None,
+            link: None,
+            group: None,
+            separator: None,
+            badge: None,
+            badge_color: None,
+            disabled: false,
         }
     }
 }
@@ -33,22 +72,39 @@ impl<T: ToString> DemandOption<T> {
 impl<T> DemandOption<T> {
     /// Create a new option with a label and item
     pub fn with_label<S: Into<String>>(label: S, item: T) -> Self {
-        static ID: AtomicUsize = AtomicUsize::new(0);
         Self {
-            id: ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            id: next_id(),
+            index: 0,
             label: label.into(),
             item,
             selected: false,
+            indeterminate: false,
             description: None,
+            key: None,
+            link: None,
+            group: None,
+            separator: None,
+            badge: None,
+            badge_color: None,
+            disabled: false,
         }
     }
     pub fn item<I>(self, item: I) -> DemandOption<I> {
         DemandOption {
             id: self.id,
+            index: self.index,
             item,
             label: self.label,
             selected: self.selected,
+            indeterminate: self.indeterminate,
             description: None,
+            key: self.key,
+            link: self.link,
+            group: self.group,
+            separator: self.separator,
+            badge: self.badge,
+            badge_color: self.badge_color,
+            disabled: self.disabled,
         }
     }
     /// Set the display label for this option.
@@ -63,6 +119,63 @@ impl<T> DemandOption<T> {
         self
     }
 
+    /// Set whether this option is shown dimmed and skipped when navigating, so it can't be
+    /// chosen or toggled.
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// Set whether this option is initially partially selected.
+    pub fn indeterminate(mut self, indeterminate: bool) -> Self {
+        self.indeterminate = indeterminate;
+        self
+    }
+
+    /// Set a shortcut key that focuses this option in a select.
+    ///
+    /// The key is drawn next to the label and takes precedence over navigation keys like `j`/`k`.
+    pub fn key(mut self, key: char) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Set a link target, making the label a clickable hyperlink in terminals that support it.
+    ///
+    /// The link is only emitted when colors are enabled. The selected value is still the item.
+    pub fn link(mut self, link: &str) -> Self {
+        self.link = Some(link.to_string());
+        self
+    }
+
+    /// Set the name of the group this option belongs to.
+    pub fn group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
+
+    /// Show a divider row such as `───` above this option in a select.
+    ///
+    /// The divider can't be focused or chosen, and is hidden while filtering.
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = Some(separator.to_string());
+        self
+    }
+
+    /// Show a short tag such as "recommended" or "beta" after the label.
+    ///
+    /// Badges are drawn in the theme's badge color and are not matched when filtering.
+    pub fn badge(mut self, badge: &str) -> Self {
+        self.badge = Some(badge.to_string());
+        self
+    }
+
+    /// Set the color of the badge, overriding the theme's badge color.
+    pub fn badge_color(mut self, color: ColorSpec) -> Self {
+        self.badge_color = Some(color);
+        self
+    }
+
     pub fn description(mut self, description: &str) -> Self {
         self.description = Some(description.to_string());
         self
@@ -76,3 +189,137 @@ impl<T: Display> PartialEq for DemandOption<T> {
 }
 
 impl<T: Display> Eq for DemandOption<T> {}
+
+impl<T> DemandOption<T> {
+    /// The number of columns the badge takes after the label, including the space before it
+    pub(crate) fn key_width(&self) -> usize {
+        self.key
+            .map_or(0, |key| 3 + console::measure_text_width(&key.to_string()))
+    }
+
+    pub(crate) fn badge_width(&self) -> usize {
+        self.badge
+            .as_ref()
+            .map_or(0, |badge| 1 + console::measure_text_width(badge))
+    }
+}
+
+/// Write the shortcut key of `option` after its label, then switch back to the `label` color
+pub(crate) fn write_key<T, W: WriteColor>(
+    out: &mut W,
+    option: &DemandOption<T>,
+    theme: &Theme,
+    label: &ColorSpec,
+) -> io::Result<()> {
+    let Some(key) = option.key else {
+        return Ok(());
+    };
+    out.set_color(&theme.help_key)?;
+    write!(out, " [{key}]")?;
+    out.set_color(label)
+}
+
+/// Write the badge of `option` after its label, then switch back to the `label` color
+pub(crate) fn write_badge<T, W: WriteColor>(
+    out: &mut W,
+    option: &DemandOption<T>,
+    theme: &Theme,
+    label: &ColorSpec,
+) -> io::Result<()> {
+    let Some(badge) = &option.badge else {
+        return Ok(());
+    };
+    out.set_color(option.badge_color.as_ref().unwrap_or(&theme.badge))?;
+    write!(out, " {badge}")?;
+    out.set_color(label)
+}
+
+/// Where option descriptions are drawn relative to their labels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DescriptionLayout {
+    /// On the same line, after the label
+    #[default]
+    Side,
+    /// On their own lines below the label, indented to line up with it
+    Below,
+}
+
+/// The number of rows each option takes up when descriptions are drawn below the labels
+pub(crate) fn rows_below<T>(options: &[DemandOption<T>]) -> usize {
+    1 + options
+        .iter()
+        .filter_map(|o| o.description.as_ref())
+        .map(|d| d.lines().count().max(1))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Write an option description on the lines below its label, indented `indent` columns
+pub(crate) fn write_description_below<W: Write>(
+    out: &mut W,
+    description: &str,
+    indent: usize,
+) -> io::Result<()> {
+    writeln!(out)?;
+    for line in description.lines() {
+        writeln!(out, "{}{line}", " ".repeat(indent))?;
+    }
+    if description.is_empty() {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Write an option description, indenting continuation lines to `indent` columns so they line
+/// up under the first
+pub(crate) fn write_description<W: Write>(
+    out: &mut W,
+    description: &str,
+    indent: usize,
+) -> io::Result<()> {
+    for (i, line) in description.lines().enumerate() {
+        match i {
+            0 => writeln!(out, "  {line}")?,
+            _ => writeln!(out, "{}  {line}", " ".repeat(indent))?,
+        }
+    }
+    if description.is_empty() {
+        writeln!(out, "  ")?;
+    }
+    Ok(())
+}
+
+/// Resolve a choice typed on stdin to the index of one of `labels`
+///
+/// Accepts a 1-based index, a label, or a prefix of exactly one label, ignoring case.
+pub(crate) fn find_option<S: AsRef<str>>(input: &str, labels: &[S]) -> io::Result<usize> {
+    let labels = labels.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    if let Ok(n) = input.parse::<usize>() {
+        return match n {
+            1.. if n <= labels.len() => Ok(n - 1),
+            _ => Err(invalid(format!("invalid selection: {input}"))),
+        };
+    }
+    let lower = input.to_lowercase();
+    if let Some(i) = labels.iter().position(|l| l.to_lowercase() == lower) {
+        return Ok(i);
+    }
+    let matches = labels
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.to_lowercase().starts_with(&lower))
+        .collect::<Vec<_>>();
+    match matches[..] {
+        [(i, _)] => Ok(i),
+        [] => Err(invalid(format!("invalid selection: {input}"))),
+        _ => Err(invalid(format!(
+            "ambiguous selection: {input} matches {}",
+            matches
+                .iter()
+                .map(|(_, l)| **l)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
@@ -0,0 +1,36 @@
+/// What a prompt was submitted with, passed to a `success_format` hook
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome<'a> {
+    /// The prompt title
+    pub title: &'a str,
+    /// The submitted values as the prompt displays them, e.g. the selected option labels
+    pub values: Vec<&'a str>,
+}
+
+impl Outcome<'_> {
+    /// The values joined the way the default success line shows them
+    pub fn value(&self) -> String {
+        self.values.join(", ")
+    }
+}
+
+/// What the line shown after submitting displays for the chosen options
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SuccessShows {
+    /// The option labels
+    #[default]
+    Label,
+    /// The items, formatted with `Display`
+    Item,
+}
+
+pub(crate) type SuccessFormatFn<'a> = Box<dyn Fn(&Outcome) -> String + 'a>;
+
+/// Render the success line with a custom `format`, ending it with a newline unless it is empty
+pub(crate) fn render(format: &SuccessFormatFn, outcome: &Outcome) -> String {
+    let mut out = format(outcome);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
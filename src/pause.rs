@@ -0,0 +1,116 @@
+use std::borrow::Cow;
+use std::io;
+use std::io::Write;
+
+use console::Term;
+use termcolor::{Buffer, WriteColor};
+
+use crate::cancel::{self, CancelToken};
+use crate::theme::Theme;
+use crate::{ctrlc, term, theme, PromptResult};
+
+/// Wait for the user to press any key
+///
+/// When stderr is not a terminal, a line is read from stdin instead.
+///
+/// # Example
+/// ```rust,no_run
+/// use demand::Pause;
+///
+/// let pause = Pause::new("Press any key to continue…");
+/// match pause.run() {
+///   Ok(_) => {}
+///   Err(e) => {
+///       if e.kind() == std::io::ErrorKind::Interrupted {
+///           println!("Pause cancelled");
+///       } else {
+///           panic!("Error: {}", e);
+///       }
+///   }
+/// };
+/// ```
+pub struct Pause<'a> {
+    /// The message to display
+    pub title: String,
+    /// The colors/style of the message
+    pub theme: Cow<'a, Theme>,
+
+    term: Term,
+    cancel_token: Option<CancelToken>,
+}
+
+impl<'a> Pause<'a> {
+    /// Create a new pause with the given message
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            theme: Cow::Owned(theme::default_theme()),
+            term: term::default_term(),
+            cancel_token: None,
+        }
+    }
+
+    /// Cancel the pause with an `Interrupted` error once `token` is cancelled, e.g. from another
+    /// thread
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Set the theme of the pause
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Displays the pause like `run`, returning whether it was submitted, cancelled or failed
+    pub fn run_result(self) -> PromptResult<()> {
+        self.run().into()
+    }
+
+    /// Displays the message and waits for a key press
+    ///
+    /// If the user presses Ctrl+C, an error of type `io::ErrorKind::Interrupted` is returned.
+    pub fn run(mut self) -> io::Result<()> {
+        let output = self.render()?;
+        if !self.term.is_term() {
+            self.term.write_all(output.as_bytes())?;
+            io::stdin().read_line(&mut String::new())?;
+            return Ok(());
+        }
+
+        let ctrlc_handle = ctrlc::show_cursor_after_ctrlc(&self.term)?;
+        self.term.hide_cursor()?;
+        self.term.write_all(output.as_bytes())?;
+        self.term.flush()?;
+        let key = cancel::read_key(&self.term, self.cancel_token.as_ref());
+        self.term.clear_last_lines(output.lines().count())?;
+        self.term.show_cursor()?;
+        ctrlc_handle.close();
+        key.map(|_| ())
+    }
+
+    fn render(&self) -> io::Result<String> {
+        let mut out = Buffer::ansi();
+        out.set_color(&self.theme.title)?;
+        writeln!(out, "{}", self.title)?;
+        out.reset()?;
+        Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::without_ansi;
+
+    #[test]
+    fn test_render() {
+        let pause = Pause::new("Press any key to continue…");
+
+        assert_eq!(
+            "Press any key to continue…\n",
+            without_ansi(pause.render().unwrap().as_str())
+        );
+    }
+}
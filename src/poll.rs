@@ -0,0 +1,105 @@
+use std::{
+    fs::File,
+    io,
+    mem::MaybeUninit,
+    os::fd::{AsRawFd, RawFd},
+    thread::sleep,
+    time::Duration,
+};
+
+use console::{Key, Term};
+
+/// Whether `read_key_timeout` really stops waiting once the timeout elapses
+pub const HAS_TIMEOUT: bool = true;
+
+/// Read a key, waiting at most `timeout` for one to be pressed
+///
+/// # Arguments
+///
+/// * `term` - The terminal to read the key from
+/// * `timeout` - How long to wait for a key press
+///
+/// # Returns
+///
+/// * `Option<Key>` - The key pressed, or `None` if the timeout elapsed first
+///
+/// # Errors
+///
+/// * `Error` - If the terminal could not be read
+///
+pub fn read_key_timeout(term: &Term, timeout: Duration) -> io::Result<Option<Key>> {
+    if !term.is_term() {
+        sleep(timeout);
+        return Ok(None);
+    }
+
+    let tty;
+    let fd = if unsafe { libc::isatty(libc::STDIN_FILENO) } == 1 {
+        libc::STDIN_FILENO
+    } else {
+        tty = File::open("/dev/tty")?;
+        tty.as_raw_fd()
+    };
+
+    // keys are only readable one at a time in raw mode, so switch to it while waiting
+    let mut original = MaybeUninit::uninit();
+    if unsafe { libc::tcgetattr(fd, original.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let original = unsafe { original.assume_init() };
+    let mut raw = original;
+    unsafe { libc::cfmakeraw(&mut raw) };
+    raw.c_oflag = original.c_oflag;
+    if unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, &raw) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let ready = wait_readable(fd, timeout);
+    if unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, &original) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    match ready? {
+        true => term.read_key().map(Some),
+        false => Ok(None),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn wait_readable(fd: RawFd, timeout: Duration) -> io::Result<bool> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+    match unsafe { libc::poll(&mut pollfd, 1, timeout) } {
+        ret if ret < 0 => Err(io::Error::last_os_error()),
+        ret => Ok(ret > 0),
+    }
+}
+
+// ttys cannot be polled on macos, only select() works
+#[cfg(target_os = "macos")]
+fn wait_readable(fd: RawFd, timeout: Duration) -> io::Result<bool> {
+    unsafe {
+        let mut read_fd_set = MaybeUninit::<libc::fd_set>::zeroed().assume_init();
+        libc::FD_ZERO(&mut read_fd_set);
+        libc::FD_SET(fd, &mut read_fd_set);
+        let mut timeout = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+        };
+        let ret = libc::select(
+            fd + 1,
+            &mut read_fd_set,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut timeout,
+        );
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(libc::FD_ISSET(fd, &read_fd_set))
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use std::{io, time::Duration};
+
+use console::{Key, Term};
+
+/// Whether `read_key_timeout` really stops waiting once the timeout elapses
+pub const HAS_TIMEOUT: bool = false;
+
+pub fn read_key_timeout(term: &Term, _timeout: Duration) -> io::Result<Option<Key>> {
+    term.read_key().map(Some)
+}
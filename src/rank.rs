@@ -0,0 +1,65 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// The first `n` of the `keyed` items, lowest key first and equal keys in their original order
+///
+/// Only `n` items are kept while scanning, so choosing a page of a large list does not sort all
+/// of it.
+pub(crate) fn top<'o, K: Ord, O>(keyed: impl Iterator<Item = (K, &'o O)>, n: usize) -> Vec<&'o O> {
+    let mut heap = BinaryHeap::new();
+    for (idx, (key, item)) in keyed.enumerate() {
+        let ranked = Ranked {
+            key: (key, idx),
+            item,
+        };
+        if heap.len() < n {
+            heap.push(ranked);
+        } else if heap.peek().is_some_and(|worst| ranked.key < worst.key) {
+            heap.pop();
+            heap.push(ranked);
+        }
+    }
+    heap.into_sorted_vec().into_iter().map(|r| r.item).collect()
+}
+
+struct Ranked<'o, K, O> {
+    key: (K, usize),
+    item: &'o O,
+}
+
+impl<K: Ord, O> PartialEq for Ranked<'_, K, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Ord, O> Eq for Ranked<'_, K, O> {}
+
+impl<K: Ord, O> PartialOrd for Ranked<'_, K, O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, O> Ord for Ranked<'_, K, O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top() {
+        let items = ["a", "b", "c", "d", "e"];
+        let scored = || [-1, -5, -3, -5, -2].into_iter().zip(items.iter());
+        assert_eq!(vec![&"b", &"d", &"c"], top(scored(), 3));
+        assert_eq!(
+            vec![&"b", &"d", &"c", &"e", &"a"],
+            top(scored(), usize::MAX)
+        );
+        assert!(top(scored(), 0).is_empty());
+    }
+}
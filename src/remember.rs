@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A value remembered for a prompt between runs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Remembered {
+    /// The label of a `Select` choice or the text of an `Input`
+    One(String),
+    /// The labels of the `MultiSelect` options that were selected
+    Many(Vec<String>),
+}
+
+/// Load the value remembered for `title`, or `None` if the file is missing or unreadable
+pub(crate) fn load(path: &Path, title: &str) -> Option<Remembered> {
+    let contents = fs::read_to_string(path).ok()?;
+    parse(&contents)?.remove(title)
+}
+
+/// Save `value` for `title`, keeping the values remembered for other titles
+///
+/// Errors are ignored so a read-only or missing config directory never affects the prompt.
+pub(crate) fn save(path: &Path, title: &str, value: Remembered) {
+    let mut values = fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| parse(&contents))
+        .unwrap_or_default();
+    values.insert(title.to_string(), value);
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, serialize(&values));
+}
+
+fn serialize(values: &BTreeMap<String, Remembered>) -> String {
+    let mut out = String::from("{\n");
+    for (i, (title, value)) in values.iter().enumerate() {
+        if i > 0 {
+            out.push_str(",\n");
+        }
+        out.push_str("  ");
+        push_string(&mut out, title);
+        out.push_str(": ");
+        match value {
+            Remembered::One(s) => push_string(&mut out, s),
+            Remembered::Many(list) => {
+                out.push('[');
+                for (j, s) in list.iter().enumerate() {
+                    if j > 0 {
+                        out.push_str(", ");
+                    }
+                    push_string(&mut out, s);
+                }
+                out.push(']');
+            }
+        }
+    }
+    out.push_str("\n}\n");
+    out
+}
+
+fn push_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Parse a JSON object whose values are strings or arrays of strings
+///
+/// Anything else makes the whole file count as unreadable.
+fn parse(contents: &str) -> Option<BTreeMap<String, Remembered>> {
+    let mut chars = contents.chars().peekable();
+    let mut values = BTreeMap::new();
+    expect(&mut chars, '{')?;
+    if skip_ws(&mut chars) == Some('}') {
+        chars.next();
+        return Some(values);
+    }
+    loop {
+        let title = parse_string(&mut chars)?;
+        expect(&mut chars, ':')?;
+        let value = match skip_ws(&mut chars)? {
+            '[' => {
+                chars.next();
+                let mut list = vec![];
+                if skip_ws(&mut chars) == Some(']') {
+                    chars.next();
+                } else {
+                    loop {
+                        list.push(parse_string(&mut chars)?);
+                        match skip_ws(&mut chars)? {
+                            ',' => chars.next(),
+                            ']' => {
+                                chars.next();
+                                break;
+                            }
+                            _ => return None,
+                        };
+                    }
+                }
+                Remembered::Many(list)
+            }
+            _ => Remembered::One(parse_string(&mut chars)?),
+        };
+        values.insert(title, value);
+        match skip_ws(&mut chars)? {
+            ',' => chars.next(),
+            '}' => return Some(values),
+            _ => return None,
+        };
+    }
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn skip_ws(chars: &mut Chars) -> Option<char> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    chars.peek().copied()
+}
+
+fn expect(chars: &mut Chars, c: char) -> Option<()> {
+    skip_ws(chars);
+    chars.next_if_eq(&c).map(|_| ())
+}
+
+fn parse_string(chars: &mut Chars) -> Option<String> {
+    expect(chars, '"')?;
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => match chars.next()? {
+                'n' => s.push('\n'),
+                'r' => s.push('\r'),
+                't' => s.push('\t'),
+                'b' => s.push('\u{8}'),
+                'f' => s.push('\u{c}'),
+                'u' => {
+                    let hex = (0..4).map(|_| chars.next()).collect::<Option<String>>()?;
+                    s.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => s.push(c),
+            },
+            c => s.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut values = BTreeMap::new();
+        values.insert(
+            "Name".to_string(),
+            Remembered::One("say \"hi\"\n".to_string()),
+        );
+        values.insert(
+            "Toppings".to_string(),
+            Remembered::Many(vec!["Lettuce".to_string(), "Jalapeños".to_string()]),
+        );
+        values.insert("None".to_string(), Remembered::Many(vec![]));
+        assert_eq!(Some(values.clone()), parse(&serialize(&values)));
+        assert_eq!(Some(BTreeMap::new()), parse(" { } "));
+        assert_eq!(None, parse("{\"Count\": 3}"));
+    }
+}
@@ -0,0 +1,54 @@
+use std::io;
+
+/// The outcome of running a prompt with `run_result`
+#[derive(Debug)]
+pub enum PromptResult<T> {
+    /// The user submitted the prompt
+    Value(T),
+    /// The user cancelled the prompt with Escape or Ctrl+C
+    Cancelled,
+    /// Reading from or writing to the terminal failed
+    Error(io::Error),
+}
+
+impl<T> PromptResult<T> {
+    /// The submitted value, or `None` if the prompt was cancelled or failed
+    pub fn value(self) -> Option<T> {
+        match self {
+            PromptResult::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Whether the user cancelled the prompt
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, PromptResult::Cancelled)
+    }
+}
+
+impl<T> From<io::Result<T>> for PromptResult<T> {
+    fn from(result: io::Result<T>) -> Self {
+        match result {
+            Ok(value) => PromptResult::Value(value),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => PromptResult::Cancelled,
+            Err(e) => PromptResult::Error(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_io_result() {
+        assert_eq!(Some(1), PromptResult::from(Ok(1)).value());
+        let cancelled = io::Error::new(io::ErrorKind::Interrupted, "user cancelled");
+        assert!(PromptResult::<()>::from(Err(cancelled)).is_cancelled());
+        let failed = io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe");
+        assert!(matches!(
+            PromptResult::<()>::from(Err(failed)),
+            PromptResult::Error(e) if e.kind() == io::ErrorKind::BrokenPipe
+        ));
+    }
+}
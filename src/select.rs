@@ -1,14 +1,44 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt::Display;
 use std::io;
-use std::io::Write;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::time::{Duration, Instant};
 
+use crate::cancel::{self, CancelToken};
+use crate::help::FooterFn;
+use crate::layout::Layout;
+use crate::outcome::{self, Outcome, SuccessFormatFn, SuccessShows};
+use crate::remember::{self, Remembered};
 use crate::theme::Theme;
-use crate::{ctrlc, theme, DemandOption};
-use console::{Alignment, Key, Term};
+use crate::truncate::{self, Truncation};
+use crate::{
+    ctrlc, event, help, option, rank, term, theme, DemandOption, DescriptionLayout, PromptEvent,
+    PromptMetrics, PromptResult,
+};
+use console::{Key, Term};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
-use itertools::Itertools;
 use termcolor::{Buffer, WriteColor};
 
+/// What a key hook set with `on_key` did with a key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyOutcome {
+    /// The key was handled
+    Consumed,
+    /// The key was not handled
+    Ignored,
+    /// The key was handled and the options changed, so paging and the cursor are recomputed
+    Rerender,
+}
+
+pub(crate) type OnKeyFn<'a, T> = Box<dyn FnMut(Key, &mut Vec<DemandOption<T>>) -> KeyOutcome + 'a>;
+pub(crate) type FilterChangeFn<'a, T> = Box<dyn FnMut(&str) -> Option<Vec<DemandOption<T>>> + 'a>;
+type SortFn<'a, T> = Box<dyn Fn(&DemandOption<T>, &DemandOption<T>) -> Ordering + 'a>;
+
 /// Select a single option from a list
 ///
 /// If multiple options are marked as selected, only the last one will be shown as selected.
@@ -43,7 +73,7 @@ pub struct Select<'a, T> {
     /// The title of the selector
     pub title: String,
     /// The colors/style of the selector
-    pub theme: &'a Theme,
+    pub theme: Cow<'a, Theme>,
     /// A description to display above the selector
     pub description: String,
     /// The options which can be selected
@@ -55,14 +85,54 @@ pub struct Select<'a, T> {
     cursor_y: usize,
     height: usize,
     term: Term,
+    cancel_token: Option<CancelToken>,
     filter: String,
     filtering: bool,
+    // whether each run starts out filtering, as set with `filtering`
+    start_filtering: bool,
     pages: usize,
     cur_page: usize,
     capacity: usize,
     fuzzy_matcher: SkimMatcherV2,
+    stream: Option<Receiver<DemandOption<T>>>,
+    sort_by: Option<SortFn<'a, T>>,
+    help_overlay: bool,
+    back_hint: bool,
+    show_system_cursor: bool,
+    focused_row: Cell<Option<usize>>,
+    escape_clears_filter: bool,
+    vim_keys: Option<bool>,
+    showing_help: bool,
+    observer: Option<Sender<PromptEvent>>,
+    success_format: Option<SuccessFormatFn<'a>>,
+    success_item: Option<fn(&T) -> String>,
+    tee: Option<Box<dyn Write + 'a>>,
+    keep_focus_on_clear: bool,
+    key_submits: bool,
+    footer: Option<FooterFn<'a>>,
+    preserve_below: bool,
+    on_key: Option<OnKeyFn<'a, T>>,
+    metrics: PromptMetrics,
+    remember: Option<PathBuf>,
+    stable_filter: bool,
+    scroll_indicators: bool,
+    fixed_position: bool,
+    layout: Layout,
+    truncation: Option<Truncation>,
+    description_layout: DescriptionLayout,
+    on_filter_change: Option<FilterChangeFn<'a, T>>,
+    filter_replaced: bool,
+    // the options from before `on_filter_change` first replaced them, restored by `reset`
+    original_options: Option<Vec<DemandOption<T>>>,
+    separator_next: bool,
 }
 
+pub(crate) const CTRL_U: char = '\u{15}';
+pub(crate) const CTRL_D: char = '\u{4}';
+
+/// How often a streaming selector checks for new options
+const STREAM_INTERVAL: Duration = Duration::from_millis(100);
+
 impl<'a, T> Select<'a, T> {
     /// Create a new select with the given title
     pub fn new<S: Into<String>>(title: S) -> Self {
@@ -71,20 +141,51 @@ impl<'a, T> Select<'a, T> {
             description: String::new(),
             options: vec![],
             filterable: false,
-            theme: &theme::DEFAULT,
+            theme: Cow::Owned(theme::default_theme()),
             cursor_x: 0,
             cursor_y: 0,
             height: 0,
-            term: Term::stderr(),
+            term: term::default_term(),
+            cancel_token: None,
             filter: String::new(),
             filtering: false,
+            start_filtering: false,
             pages: 0,
             cur_page: 0,
             capacity: 0,
             fuzzy_matcher: SkimMatcherV2::default().use_cache(true).smart_case(),
+            stream: None,
+            sort_by: None,
+            help_overlay: false,
+            back_hint: false,
+            show_system_cursor: false,
+            focused_row: Cell::new(None),
+            escape_clears_filter: true,
+            vim_keys: None,
+            showing_help: false,
+            observer: None,
+            success_format: None,
+            success_item: None,
+            tee: None,
+            keep_focus_on_clear: false,
+            key_submits: false,
+            footer: None,
+            preserve_below: false,
+            on_key: None,
+            metrics: PromptMetrics::default(),
+            remember: None,
+            stable_filter: false,
+            scroll_indicators: false,
+            fixed_position: false,
+            layout: Layout::default(),
+            truncation: None,
+            description_layout: DescriptionLayout::default(),
+            on_filter_change: None,
+            filter_replaced: false,
+            original_options: None,
+            separator_next: false,
         };
-        let max_height = s.term.size().0 as usize;
-        s.capacity = max_height.max(8) - 6;
+        s.fit_capacity();
         s
     }
 
@@ -96,7 +197,21 @@ impl<'a, T> Select<'a, T> {
 
     /// Add an option to the selector
     pub fn option(mut self, option: DemandOption<T>) -> Self {
-        self.options.push(option);
+        self.push_option(option);
+        self.pages = self.get_pages();
+        self.cursor_y = self.get_selected_option_idx();
+        self
+    }
+
+    /// Add multiple options to the selector from `(label, item)` pairs, in order
+    pub fn options_from<I, S>(mut self, options: I) -> Self
+    where
+        I: IntoIterator<Item = (S, T)>,
+        S: Into<String>,
+    {
+        for (label, item) in options {
+            self.push_option(DemandOption::with_label(label, item));
+        }
         self.pages = self.get_pages();
         self.cursor_y = self.get_selected_option_idx();
         self
@@ -105,13 +220,73 @@ impl<'a, T> Select<'a, T> {
     /// Add multiple options to the selector
     pub fn options(mut self, options: Vec<DemandOption<T>>) -> Self {
         for option in options {
-            self.options.push(option);
+            self.push_option(option);
+        }
+        self.pages = self.get_pages();
+        self.cursor_y = self.get_selected_option_idx();
+        self
+    }
+
+    /// Add `options` under a header row reading `name`
+    ///
+    /// The header can't be focused or chosen, and the options are indented below it. While
+    /// filtering, headers are only shown above matching options.
+    pub fn group(mut self, name: &str, options: Vec<DemandOption<T>>) -> Self {
+        for option in options {
+            self.push_option(option.group(name));
         }
         self.pages = self.get_pages();
         self.cursor_y = self.get_selected_option_idx();
         self
     }
 
+    /// Show a `───` divider row between the options added before and after this call
+    pub fn separator(mut self) -> Self {
+        self.separator_next = true;
+        self
+    }
+
+    /// Set the description of each option added so far from `describe`
+    ///
+    /// Options for which `describe` returns `None` keep their current description.
+    pub fn describe_with(mut self, describe: impl Fn(&DemandOption<T>) -> Option<String>) -> Self {
+        for option in &mut self.options {
+            if let Some(description) = describe(option) {
+                option.description = Some(description);
+            }
+        }
+        self
+    }
+
+    /// Mutable access to the options of the selector
+    ///
+    /// Paging and the initially highlighted option are recomputed when the selector runs.
+    pub fn options_mut(&mut self) -> &mut Vec<DemandOption<T>> {
+        &mut self.options
+    }
+
+    /// Show the options in the order given by `compare` instead of the order they were added
+    ///
+    /// Filtering still orders matches by how well they match. A separator stays above the option
+    /// it was added before.
+    pub fn sort_by<F>(mut self, compare: F) -> Self
+    where
+        F: Fn(&DemandOption<T>, &DemandOption<T>) -> Ordering + 'a,
+    {
+        self.sort_by = Some(Box::new(compare));
+        self
+    }
+
+    /// Stream options into the selector while it is running
+    ///
+    /// Options sent on the channel are appended and shown as they arrive, so the selector can be
+    /// displayed before a slow data source has finished loading. Streaming stops once the sender
+    /// is dropped. On Windows new options only show up after the next key press.
+    pub fn stream(mut self, receiver: Receiver<DemandOption<T>>) -> Self {
+        self.stream = Some(receiver);
+        self
+    }
+
     /// Set whether the selector can be filtered with a query
     pub fn filterable(mut self, filterable: bool) -> Self {
         self.filterable = filterable;
@@ -121,150 +296,704 @@ impl<'a, T> Select<'a, T> {
     /// Start filtering immediately
     pub fn filtering(mut self, filtering: bool) -> Self {
         self.filtering = filtering;
+        self.start_filtering = filtering;
+        self
+    }
+
+    /// Report the outcome of the prompt to `observer` in addition to returning it
+    pub fn observe(mut self, observer: Sender<PromptEvent>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Set whether typing an option's shortcut key submits it immediately instead of only
+    /// focusing it
+    pub fn key_submits(mut self, key_submits: bool) -> Self {
+        self.key_submits = key_submits;
+        self
+    }
+
+    /// Set whether content printed below the prompt is kept on submit instead of being cleared
+    pub fn preserve_below(mut self, preserve_below: bool) -> Self {
+        self.preserve_below = preserve_below;
+        self
+    }
+
+    /// Set a hook called with the filter query each time it changes
+    ///
+    /// Returning options replaces the selector's options, e.g. with results fetched for the query.
+    /// Once it has replaced them, they are shown as returned, without local fuzzy filtering, until
+    /// the filter is cleared.
+    /// Returning `None` keeps the current options.
+    pub fn on_filter_change<F>(mut self, on_filter_change: F) -> Self
+    where
+        F: FnMut(&str) -> Option<Vec<DemandOption<T>>> + 'a,
+    {
+        self.on_filter_change = Some(Box::new(on_filter_change));
+        self
+    }
+
+    /// Set a hook called with keys the selector doesn't handle itself
+    ///
+    /// The hook can change the options, e.g. to refresh them, and should then return
+    /// `KeyOutcome::Rerender`.
+    pub fn on_key<F>(mut self, on_key: F) -> Self
+    where
+        F: FnMut(Key, &mut Vec<DemandOption<T>>) -> KeyOutcome + 'a,
+    {
+        self.on_key = Some(Box::new(on_key));
+        self
+    }
+
+    /// Set whether clearing the filter keeps the focused option focused instead of returning to
+    /// the first option
+    pub fn keep_focus_on_clear(mut self, keep_focus_on_clear: bool) -> Self {
+        self.keep_focus_on_clear = keep_focus_on_clear;
+        self
+    }
+
+    /// Set whether filtering keeps matches in their original order instead of sorting them by
+    /// how well they match
+    pub fn stable_filter(mut self, stable_filter: bool) -> Self {
+        self.stable_filter = stable_filter;
+        self
+    }
+
+    /// Set whether a `↑ more` or `↓ more` line is shown when there are options on earlier or
+    /// later pages
+    pub fn scroll_indicators(mut self, scroll_indicators: bool) -> Self {
+        self.scroll_indicators = scroll_indicators;
+        self.fit_capacity();
+        self
+    }
+
+    /// Set whether the selector is drawn at a fixed position, so running it repeatedly does not
+    /// move down the screen
+    ///
+    /// The cursor position is saved when the selector starts, each redraw clears from there to the
+    /// end of the screen, and the cursor is returned there once it finishes. This is meant for
+    /// live dashboards; if drawing scrolls the terminal, the saved position no longer lines up.
+    pub fn fixed_position(mut self, fixed_position: bool) -> Self {
+        self.fixed_position = fixed_position;
+        self
+    }
+
+    /// Set a static line to display beneath the help keys
+    pub fn footer(mut self, footer: &str) -> Self {
+        let footer = footer.to_string();
+        self.footer = Some(Box::new(move || footer.clone()));
+        self
+    }
+
+    /// Set a status line to display beneath the help keys, recomputed each time it is drawn
+    pub fn status<F: Fn() -> String + 'a>(mut self, status: F) -> Self {
+        self.footer = Some(Box::new(status));
+        self
+    }
+
+    /// Set whether pressing `?` shows an overlay listing every keybinding
+    pub fn enable_help_overlay(mut self, help_overlay: bool) -> Self {
+        self.help_overlay = help_overlay;
+        self
+    }
+
+    /// Set whether to show an "esc back" hint, for flows that step back when the prompt is cancelled
+    pub fn back_hint(mut self, back_hint: bool) -> Self {
+        self.back_hint = back_hint;
+        self
+    }
+
+    /// Set whether to keep the terminal's cursor visible on the focused option instead of hiding
+    /// it, for screen readers and terminals that track the real cursor
+    pub fn show_system_cursor(mut self, show_system_cursor: bool) -> Self {
+        self.show_system_cursor = show_system_cursor;
+        self
+    }
+
+    /// Set whether vim-style navigation keys are bound
+    ///
+    /// When true, `g`/`G` jump to the first/last option and Ctrl+D/Ctrl+U move half a page down/up.
+    /// When false, `h`/`j`/`k`/`l` are unbound too, leaving only the arrow keys. By default only
+    /// `h`/`j`/`k`/`l` are bound.
+    pub fn vim_keys(mut self, vim_keys: bool) -> Self {
+        self.vim_keys = Some(vim_keys);
+        self
+    }
+
+    /// Set whether Escape clears the filter before cancelling (default: true)
+    ///
+    /// When false, Escape always cancels the prompt and Ctrl+U clears the filter instead.
+    pub fn escape_clears_filter(mut self, escape_clears_filter: bool) -> Self {
+        self.escape_clears_filter = escape_clears_filter;
+        self
+    }
+
+    /// Remember the chosen option in the JSON file at `path`, keyed by title, and focus it the
+    /// next time the selector runs
+    ///
+    /// Failures to read or write the file are ignored.
+    pub fn remember(mut self, path: &Path) -> Self {
+        self.remember = Some(path.to_path_buf());
+        self
+    }
+
+    /// Set the maximum width of the selector, wrapping longer lines
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.layout.max_width = Some(max_width);
+        self
+    }
+
+    /// Shorten labels too wide for the terminal with `truncation` instead of letting them wrap
+    pub fn truncate(mut self, truncation: Truncation) -> Self {
+        self.truncation = Some(truncation);
+        self
+    }
+
+    /// Set whether option descriptions are drawn beside the labels or on their own line below
+    pub fn description_layout(mut self, layout: DescriptionLayout) -> Self {
+        self.description_layout = layout;
+        self
+    }
+
+    /// Set whether the selector is centered horizontally in the terminal
+    pub fn center(mut self, center: bool) -> Self {
+        self.layout.center = center;
+        self
+    }
+
+    /// Cancel the selector with an `Interrupted` error once `token` is cancelled, e.g. from another
+    /// thread
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
         self
     }
 
     /// Set the theme of the selector
-    pub fn theme(mut self, theme: &'a Theme) -> Self {
-        self.theme = theme;
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
         self
     }
 
+    /// Set how the line shown after submitting is formatted, replacing the default "Title value"
+    ///
+    /// Return an empty string to show nothing.
+    pub fn success_format(mut self, format: impl Fn(&Outcome) -> String + 'a) -> Self {
+        self.success_format = Some(Box::new(format));
+        self
+    }
+
+    /// Also write the line shown after submitting to `sink`, without colors
+    ///
+    /// Errors writing to `sink` are ignored.
+    pub fn tee(mut self, sink: Box<dyn Write + 'a>) -> Self {
+        self.tee = Some(sink);
+        self
+    }
+
+    /// The option currently under the cursor, or `None` if no options are shown
+    pub fn highlighted(&self) -> Option<&DemandOption<T>> {
+        self.visible_options().get(self.cursor_y).copied()
+    }
+
+    /// Whether `run` will draw the selector, rather than listing the options and reading
+    /// the choice from stdin because its terminal is not a TTY
+    pub fn is_interactive(&self) -> bool {
+        self.term.is_term()
+    }
+
+    /// Displays the selector like `run`, returning whether it was submitted, cancelled or failed
+    pub fn run_result(self) -> PromptResult<T> {
+        self.run().into()
+    }
+
     /// Displays the selector to the user and returns their selected options
     ///
     /// This function will block until the user submits the input. If the user cancels the input,
     /// an error of type `io::ErrorKind::Interrupted` is returned.
-    pub fn run(mut self) -> io::Result<T> {
+    pub fn run(self) -> io::Result<T> {
+        self.run_with_metrics().map(|(item, _)| item)
+    }
+
+    /// Displays the selector like `run`, also returning how the user interacted with it
+    pub fn run_with_metrics(self) -> io::Result<(T, PromptMetrics)> {
+        self.run_all().map(|(_, item, metrics)| (item, metrics))
+    }
+
+    /// Displays the selector like `run`, also returning the position of the chosen option
+    ///
+    /// The position counts from 0 in the order the options were added, regardless of filtering
+    /// or `sort_by`.
+    pub fn run_with_index(self) -> io::Result<(usize, T)> {
+        self.run_all().map(|(index, item, _)| (index, item))
+    }
+
+    fn run_all(mut self) -> io::Result<(usize, T, PromptMetrics)> {
+        let (id, metrics) = self.run_in_place()?;
+        let index = self.insertion_index(id);
+        let selected = self.options.into_iter().find(|o| o.id == id).unwrap();
+        Ok((index, selected.item, metrics))
+    }
+
+    /// Runs the selector without consuming it, returning the id of the chosen option
+    fn run_in_place(&mut self) -> io::Result<(usize, PromptMetrics)> {
+        let started = Instant::now();
+        self.reset();
+        self.prepare();
+        if !self.term.is_term() {
+            let mut term = self.term.clone();
+            return self.read_non_tty(started, &mut io::stdin().lock(), &mut term);
+        }
+
         let ctrlc_handle = ctrlc::show_cursor_after_ctrlc(&self.term)?;
+        if self.fixed_position {
+            term::save_cursor(&self.term)?;
+        }
+
+        self.fit_capacity();
+        self.pages = self.get_pages();
+        self.cursor_y = self.get_selected_option_idx();
+        self.focus_enabled(true)?;
 
         loop {
             self.clear()?;
             let output = self.render()?;
+            let width = self.term.size().1 as usize;
+            let focused_row = self.focused_row.get();
+            // wrapping the lines above the focused option moves it down
+            self.focused_row
+                .set(focused_row.map(|row| self.layout.row(&output, row, width)));
+            let output = self.layout.apply(output, width);
             self.term.write_all(output.as_bytes())?;
             self.term.flush()?;
-            self.term.hide_cursor()?;
+            if !self.show_system_cursor {
+                self.term.hide_cursor()?;
+            }
             self.height = output.lines().count() - 1;
-            let enter = |mut select: Select<T>| {
-                select.clear()?;
-                select.term.show_cursor()?;
-                let id = select.visible_options().get(select.cursor_y).unwrap().id;
-                let selected = select.options.iter().find(|o| o.id == id).unwrap();
-                let output = select.render_success(&selected.label)?;
-                let selected = select.options.into_iter().find(|o| o.id == id).unwrap();
-                select.term.write_all(output.as_bytes())?;
-                select.term.clear_to_end_of_screen()?;
-                Ok::<T, io::Error>(selected.item)
+            let key = match self.read_key_at_focus(&output)? {
+                Some(key) => key,
+                None => continue,
             };
-
+            self.metrics.keystrokes += 1;
+            if self.showing_help {
+                self.showing_help = false;
+                continue;
+            }
             if self.filtering {
-                match self.term.read_key()? {
+                match key {
                     Key::ArrowDown => self.handle_down()?,
                     Key::ArrowUp => self.handle_up()?,
                     Key::ArrowLeft => self.handle_left()?,
                     Key::ArrowRight => self.handle_right()?,
-                    Key::Enter => return enter(self),
-                    Key::Escape => self.handle_stop_filtering(false)?,
+                    Key::Enter if self.can_submit() => return self.submit(started),
+                    Key::Escape if self.escape_clears_filter => {
+                        self.handle_stop_filtering(false)?
+                    }
+                    Key::Escape => {
+                        ctrlc_handle.close();
+                        return self.cancel();
+                    }
                     Key::Backspace => self.handle_filter_backspace()?,
+                    Key::Char(CTRL_U) => self.handle_filter_ctrl_u()?,
                     Key::Char(c) => self.handle_filter_key(c)?,
                     _ => {}
                 }
             } else {
-                match self.term.read_key()? {
-                    Key::ArrowDown | Key::Char('j') => self.handle_down()?,
-                    Key::ArrowUp | Key::Char('k') => self.handle_up()?,
-                    Key::ArrowLeft | Key::Char('h') => self.handle_left()?,
-                    Key::ArrowRight | Key::Char('l') => self.handle_right()?,
-                    Key::Char('/') if self.filterable => self.handle_start_filtering(),
-                    Key::Escape => {
-                        if self.filter.is_empty() {
-                            self.term.show_cursor()?;
+                match key {
+                    Key::ArrowDown => self.handle_down()?,
+                    Key::ArrowUp => self.handle_up()?,
+                    Key::ArrowLeft => self.handle_left()?,
+                    Key::ArrowRight => self.handle_right()?,
+                    // option shortcuts take precedence over the navigation keys
+                    Key::Char(c) if self.option_with_key(c).is_some() => {
+                        self.handle_option_key(c);
+                        if self.key_submits {
                             ctrlc_handle.close();
-                            return Err(io::Error::new(
-                                io::ErrorKind::Interrupted,
-                                "user cancelled",
-                            ));
+                            return self.submit(started);
                         }
-                        self.handle_stop_filtering(false)?;
                     }
-                    Key::Enter => {
+                    Key::Char('j') if self.vim_keys != Some(false) => self.handle_down()?,
+                    Key::Char('k') if self.vim_keys != Some(false) => self.handle_up()?,
+                    Key::Char('h') if self.vim_keys != Some(false) => self.handle_left()?,
+                    Key::Char('l') if self.vim_keys != Some(false) => self.handle_right()?,
+                    Key::Char('g') if self.vim_keys == Some(true) => self.handle_first()?,
+                    Key::Char('G') if self.vim_keys == Some(true) => self.handle_last()?,
+                    Key::Char(CTRL_D) if self.vim_keys == Some(true) => {
+                        self.handle_half_page(true)?
+                    }
+                    Key::Char(CTRL_U) if self.vim_keys == Some(true) => {
+                        self.handle_half_page(false)?
+                    }
+                    Key::Char('/') if self.filterable => self.handle_start_filtering(),
+                    Key::Char('?') if self.help_overlay => self.showing_help = true,
+                    Key::Escape if self.filter.is_empty() || !self.escape_clears_filter => {
                         ctrlc_handle.close();
-                        return enter(self);
+                        return self.cancel();
                     }
-                    _ => {}
+                    Key::Escape => self.handle_stop_filtering(false)?,
+                    Key::Enter if self.can_submit() => {
+                        ctrlc_handle.close();
+                        return self.submit(started);
+                    }
+                    _ => self.handle_on_key(key),
                 }
             }
         }
     }
 
-    fn filtered_options(&self) -> Vec<&DemandOption<T>> {
-        self.options
-            .iter()
-            .filter_map(|opt| {
-                if self.filter.is_empty() {
-                    Some((0, opt))
-                } else {
-                    self.fuzzy_matcher
-                        .fuzzy_match(&opt.label.to_lowercase(), &self.filter.to_lowercase())
-                        .map(|score| (score, opt))
+    /// Shows the success line for the focused option and returns its id
+    fn submit(&mut self, started: Instant) -> io::Result<(usize, PromptMetrics)> {
+        self.clear()?;
+        self.term.show_cursor()?;
+        let id = self.visible_options().get(self.cursor_y).unwrap().id;
+        let selected = self.options.iter().find(|o| o.id == id).unwrap();
+        let output = self.render_success(&self.success_text(selected))?;
+        let output = self.layout.apply(output, self.term.size().1 as usize);
+        let metrics = PromptMetrics {
+            elapsed: started.elapsed(),
+            ..self.metrics.clone()
+        };
+        event::submitted(&self.observer, &self.title, &selected.label);
+        if let Some(path) = &self.remember {
+            remember::save(path, &self.title, Remembered::One(selected.label.clone()));
+        }
+        self.term.write_all(output.as_bytes())?;
+        term::record_height(&self.term, &output);
+        term::tee(&mut self.tee, &output);
+        if !self.preserve_below {
+            self.term.clear_to_end_of_screen()?;
+        }
+        if self.fixed_position {
+            term::restore_cursor(&self.term)?;
+        }
+        Ok((id, metrics))
+    }
+
+    /// Clears the cursor, page and filter left over from a previous run, along with any options
+    /// the filter hook swapped in
+    fn reset(&mut self) {
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+        self.cur_page = 0;
+        self.filter.clear();
+        self.filtering = self.start_filtering;
+        self.filter_replaced = false;
+        if let Some(options) = self.original_options.take() {
+            self.options = options;
+        }
+        self.showing_help = false;
+        self.height = 0;
+        self.metrics = PromptMetrics::default();
+    }
+
+    /// Reads the next key like `read_key`, with the cursor on the focused option while waiting if
+    /// `show_system_cursor` is set and the filter isn't being typed
+    fn read_key_at_focus(&mut self, output: &str) -> io::Result<Option<Key>> {
+        let focused = self.show_system_cursor && !self.filtering;
+        let Some(row) = self.focused_row.get().filter(|_| focused) else {
+            return self.read_key();
+        };
+        let lift = term::lift_cursor(&self.term, output, row)?;
+        let key = self.read_key();
+        self.term.move_cursor_down(lift)?;
+        key
+    }
+
+    /// Reads the next key, or returns `None` when streamed options arrived and need rendering
+    fn read_key(&mut self) -> io::Result<Option<Key>> {
+        let Some(stream) = &self.stream else {
+            return cancel::read_key(&self.term, self.cancel_token.as_ref()).map(Some);
+        };
+        let mut received = false;
+        let mut disconnected = false;
+        loop {
+            match stream.try_recv() {
+                Ok(mut option) => {
+                    option.index = self.options.len();
+                    self.options.push(option);
+                    received = true;
                 }
-            })
-            .sorted_by_key(|(score, _opt)| -1 * *score)
-            .map(|(_score, opt)| opt)
-            .collect()
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+        if disconnected {
+            self.stream = None;
+        }
+        if received {
+            self.pages = self.get_pages();
+            if self.sort_by.is_some() {
+                let focused = self.highlighted().map(|o| o.id);
+                self.sort_options();
+                if let Some(id) = focused {
+                    self.focus_option(id);
+                }
+            }
+            return Ok(None);
+        }
+        cancel::read_key_timeout(&self.term, self.cancel_token.as_ref(), STREAM_INTERVAL)
+    }
+
+    fn sort_options(&mut self) {
+        if let Some(compare) = &self.sort_by {
+            self.options.sort_by(|a, b| compare(a, b));
+        }
+    }
+
+    /// Numbers the options by their current position, which is the order they were added in as
+    /// long as they haven't been sorted
+    fn number_options(&mut self) {
+        for (index, option) in self.options.iter_mut().enumerate() {
+            option.index = index;
+        }
+    }
+
+    /// The position option `id` was added at
+    fn insertion_index(&self, id: usize) -> usize {
+        self.options.iter().find(|o| o.id == id).unwrap().index
+    }
+
+    fn filtered_options(&self) -> Vec<&DemandOption<T>> {
+        self.ranked_options(usize::MAX)
+    }
+
+    /// The first `n` options matching the filter, best match first unless `stable_filter` is set
+    fn ranked_options(&self, n: usize) -> Vec<&DemandOption<T>> {
+        if self.filter.is_empty() || self.filter_replaced {
+            return self.options.iter().take(n).collect();
+        }
+        let filter = self.filter.to_lowercase();
+        let matches = self.options.iter().filter_map(|opt| {
+            self.fuzzy_matcher
+                .fuzzy_match(&opt.label.to_lowercase(), &filter)
+                .map(|score| (score, opt))
+        });
+        if self.stable_filter {
+            return matches.map(|(_score, opt)| opt).take(n).collect();
+        }
+        rank::top(matches.map(|(score, opt)| (-score, opt)), n)
+    }
+
+    /// Runs the selector as if its terminal were not a TTY, reading the choice from `input`
+    /// instead of stdin
+    ///
+    /// Returns the chosen option and the listing that would have been written to the terminal,
+    /// so how piped input is parsed can be tested without a subprocess.
+    pub fn run_non_tty(mut self, input: &str) -> io::Result<(T, String)> {
+        self.prepare();
+        let mut output = vec![];
+        let (id, _) = self.read_non_tty(Instant::now(), &mut input.as_bytes(), &mut output)?;
+        let selected = self.options.into_iter().find(|o| o.id == id).unwrap();
+        Ok((selected.item, String::from_utf8_lossy(&output).into_owned()))
+    }
+
+    /// Orders the options and focuses the remembered one before running
+    fn prepare(&mut self) {
+        // options may have been added through `options_mut`, but none are sorted yet
+        self.number_options();
+        self.sort_options();
+        self.restore_remembered();
+    }
+
+    /// Lists the options to `output` and reads a 1-based index or a label from `input`
+    ///
+    /// An empty line picks the option that would have been focused.
+    fn read_non_tty(
+        &mut self,
+        started: Instant,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> io::Result<(usize, PromptMetrics)> {
+        let mut out = String::new();
+        out.push_str(&format!("{}\n", self.title));
+        if !self.description.is_empty() {
+            out.push_str(&format!("{}\n", self.description));
+        }
+        let options = self.filtered_options();
+        if options.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no options to select",
+            ));
+        }
+        for (i, option) in options.iter().enumerate() {
+            out.push_str(&format!("  {}. {}\n", i + 1, option.label));
+        }
+        out.push_str("> ");
+        output.write_all(out.as_bytes())?;
+        output.flush()?;
+
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        let idx = match line.trim() {
+            "" => options.iter().rposition(|o| o.selected).unwrap_or(0),
+            input => {
+                let labels = options.iter().map(|o| o.label.as_str()).collect::<Vec<_>>();
+                option::find_option(input, &labels)?
+            }
+        };
+        let id = options[idx].id;
+        let label = options[idx].label.clone();
+        if options[idx].disabled {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("option is disabled: {label}"),
+            ));
+        }
+        event::submitted(&self.observer, &self.title, &label);
+        if let Some(path) = &self.remember {
+            remember::save(path, &self.title, Remembered::One(label));
+        }
+        let metrics = PromptMetrics {
+            elapsed: started.elapsed(),
+            ..Default::default()
+        };
+        Ok((id, metrics))
     }
 
     fn visible_options(&self) -> Vec<&DemandOption<T>> {
-        let filtered_options = self.filtered_options();
         let start = self.cur_page * self.capacity;
-        filtered_options
+        self.ranked_options(start + self.capacity)
             .into_iter()
             .skip(start)
-            .take(self.capacity)
             .collect()
     }
 
     fn handle_down(&mut self) -> Result<(), io::Error> {
-        let visible_options = self.visible_options();
-        if self.cursor_y < visible_options.len().max(1) - 1 {
+        self.metrics.navigations += 1;
+        let from = (self.cur_page, self.cursor_y);
+        if self.step_down()? {
+            self.focus_enabled(true)?;
+        }
+        if self.focused_disabled() {
+            (self.cur_page, self.cursor_y) = from;
+        }
+        Ok(())
+    }
+
+    fn handle_up(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        let from = (self.cur_page, self.cursor_y);
+        if self.step_up()? {
+            self.focus_enabled(false)?;
+        }
+        if self.focused_disabled() {
+            (self.cur_page, self.cursor_y) = from;
+        }
+        Ok(())
+    }
+
+    /// Moves the cursor to the next option, returning false when it is already on the last
+    fn step_down(&mut self) -> io::Result<bool> {
+        if self.cursor_y < self.visible_options().len().max(1) - 1 {
             self.cursor_y += 1;
         } else if self.pages > 0 && self.cur_page < self.pages - 1 {
             self.cur_page += 1;
             self.cursor_y = 0;
             self.term.clear_to_end_of_screen()?;
+        } else {
+            return Ok(false);
         }
-        Ok(())
+        Ok(true)
     }
 
-    fn handle_up(&mut self) -> Result<(), io::Error> {
+    /// Moves the cursor to the previous option, returning false when it is already on the first
+    fn step_up(&mut self) -> io::Result<bool> {
         if self.cursor_y > 0 {
             self.cursor_y -= 1;
         } else if self.cur_page > 0 {
             self.cur_page -= 1;
             self.cursor_y = self.visible_options().len().max(1) - 1;
             self.term.clear_to_end_of_screen()?;
+        } else {
+            return Ok(false);
         }
-        Ok(())
+        Ok(true)
     }
 
-    fn handle_left(&mut self) -> Result<(), io::Error> {
-        if self.filtering {
-            if self.cursor_x > 0 {
-                self.cursor_x -= 1;
+    /// Moves the cursor down or up off disabled options, staying put if there are only disabled
+    /// options that way
+    fn focus_enabled(&mut self, down: bool) -> io::Result<()> {
+        let from = (self.cur_page, self.cursor_y);
+        while self.focused_disabled() {
+            let moved = match down {
+                true => self.step_down()?,
+                false => self.step_up()?,
+            };
+            if !moved {
+                (self.cur_page, self.cursor_y) = from;
+                break;
             }
-        } else if self.cur_page > 0 {
-            self.cur_page -= 1;
-            self.term.clear_to_end_of_screen()?;
         }
         Ok(())
     }
 
-    fn handle_right(&mut self) -> Result<(), io::Error> {
-        if self.filtering {
-            if self.cursor_x < self.filter.chars().count() {
-                self.cursor_x += 1;
-            }
-        } else if self.pages > 0 && self.cur_page < self.pages - 1 {
-            self.cur_page += 1;
+    fn focused_disabled(&self) -> bool {
+        self.highlighted().is_some_and(|o| o.disabled)
+    }
+
+    /// Whether enter would choose the focused option
+    fn can_submit(&self) -> bool {
+        self.highlighted().is_some_and(|o| !o.disabled)
+    }
+
+    fn handle_first(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        if self.cur_page > 0 {
+            self.cur_page = 0;
+            self.term.clear_to_end_of_screen()?;
+        }
+        self.cursor_y = 0;
+        self.focus_enabled(true)
+    }
+
+    fn handle_last(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        let last_page = self.pages.saturating_sub(1);
+        if self.cur_page < last_page {
+            self.cur_page = last_page;
+            self.term.clear_to_end_of_screen()?;
+        }
+        self.cursor_y = self.visible_options().len().max(1) - 1;
+        self.focus_enabled(false)
+    }
+
+    /// Move half a page down or up, crossing onto the next or previous page as needed
+    fn handle_half_page(&mut self, down: bool) -> Result<(), io::Error> {
+        let navigations = self.metrics.navigations;
+        for _ in 0..(self.capacity / 2).max(1) {
+            match down {
+                true => self.handle_down()?,
+                false => self.handle_up()?,
+            }
+        }
+        self.metrics.navigations = navigations + 1;
+        Ok(())
+    }
+
+    fn handle_left(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        if self.filtering {
+            if self.cursor_x > 0 {
+                self.cursor_x -= 1;
+            }
+        } else if self.cur_page > 0 {
+            self.cur_page -= 1;
+            self.term.clear_to_end_of_screen()?;
+        }
+        Ok(())
+    }
+
+    fn handle_right(&mut self) -> Result<(), io::Error> {
+        self.metrics.navigations += 1;
+        if self.filtering {
+            if self.cursor_x < self.filter.chars().count() {
+                self.cursor_x += 1;
+            }
+        } else if self.pages > 0 && self.cur_page < self.pages - 1 {
+            self.cur_page += 1;
             if self.cursor_y > self.visible_options().len() - 1 {
                 self.cursor_y = self.visible_options().len() - 1;
             }
@@ -274,27 +1003,119 @@ impl<'a, T> Select<'a, T> {
     }
 
     fn handle_start_filtering(&mut self) {
+        self.metrics.filtered = true;
         self.filtering = true;
     }
 
+    /// Cancels the prompt, as when Escape is pressed with no filter to clear
+    fn cancel<R>(&mut self) -> io::Result<R> {
+        if self.fixed_position {
+            self.clear()?;
+        }
+        self.term.show_cursor()?;
+        event::cancelled(&self.observer, &self.title);
+        Err(io::Error::new(io::ErrorKind::Interrupted, "user cancelled"))
+    }
+
     fn handle_stop_filtering(&mut self, save: bool) -> Result<(), io::Error> {
+        let focused = self.visible_options().get(self.cursor_y).map(|o| o.id);
         self.filtering = false;
         self.cur_page = 0;
 
         if !save {
             self.filter.clear();
+            self.handle_filter_change();
             self.pages = self.get_pages();
+            if let Some(id) = focused.filter(|_| self.keep_focus_on_clear) {
+                self.focus_option(id);
+            }
         }
         self.term.clear_to_end_of_screen()
     }
 
+    /// The id of the visible, enabled option with shortcut `key`
+    fn option_with_key(&self, key: char) -> Option<usize> {
+        self.filtered_options()
+            .iter()
+            .find(|o| o.key == Some(key) && !o.disabled)
+            .map(|o| o.id)
+    }
+
+    /// Focuses the visible option with shortcut `key`, returning whether one was found
+    fn handle_option_key(&mut self, key: char) -> bool {
+        match self.option_with_key(key) {
+            Some(id) => {
+                self.focus_option(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn handle_on_key(&mut self, key: Key) {
+        let Some(on_key) = &mut self.on_key else {
+            return;
+        };
+        if on_key(key, &mut self.options) == KeyOutcome::Rerender {
+            self.pages = self.get_pages();
+            self.cur_page = self.cur_page.min(self.pages.max(1) - 1);
+            self.cursor_y = self.cursor_y.min(self.visible_options().len().max(1) - 1);
+        }
+    }
+
+    fn focus_option(&mut self, id: usize) {
+        if let Some(idx) = self.filtered_options().iter().position(|o| o.id == id) {
+            self.cur_page = idx / self.capacity;
+            self.cursor_y = idx % self.capacity;
+        }
+    }
+
+    fn push_option(&mut self, mut option: DemandOption<T>) {
+        if std::mem::take(&mut self.separator_next) && option.separator.is_none() {
+            option.separator = Some(theme::glyph("───", "---").to_string());
+        }
+        option.index = self.options.len();
+        self.options.push(option);
+    }
+
+    /// Passes the new filter to the `on_filter_change` hook, replacing the options it returns
+    fn handle_filter_change(&mut self) {
+        let Some(on_filter_change) = &mut self.on_filter_change else {
+            return;
+        };
+        if let Some(options) = on_filter_change(&self.filter) {
+            let replaced = std::mem::replace(&mut self.options, options);
+            self.original_options.get_or_insert(replaced);
+            self.number_options();
+            self.filter_replaced = true;
+        }
+        if self.filter.is_empty() {
+            self.filter_replaced = false;
+        }
+    }
+
     fn handle_filter_key(&mut self, c: char) -> Result<(), io::Error> {
         let idx = self.get_char_idx(&self.filter, self.cursor_x);
         self.filter.insert(idx, c);
         self.cursor_x += 1;
+        self.handle_filter_change();
+        self.cursor_y = 0;
+        self.cur_page = 0;
+        self.pages = self.get_pages();
+        self.focus_enabled(true)?;
+        self.term.clear_to_end_of_screen()
+    }
+
+    /// Deletes the filter before the cursor, staying in filtering mode
+    fn handle_filter_ctrl_u(&mut self) -> Result<(), io::Error> {
+        let idx = self.get_char_idx(&self.filter, self.cursor_x);
+        self.filter.replace_range(..idx, "");
+        self.cursor_x = 0;
+        self.handle_filter_change();
         self.cursor_y = 0;
         self.cur_page = 0;
         self.pages = self.get_pages();
+        self.focus_enabled(true)?;
         self.term.clear_to_end_of_screen()
     }
 
@@ -307,9 +1128,11 @@ impl<'a, T> Select<'a, T> {
         if self.cursor_x > 0 {
             self.cursor_x -= 1;
         }
+        self.handle_filter_change();
         self.cursor_y = 0;
         self.cur_page = 0;
         self.pages = self.get_pages();
+        self.focus_enabled(true)?;
         self.term.clear_to_end_of_screen()
     }
 
@@ -317,6 +1140,48 @@ impl<'a, T> Select<'a, T> {
         ((self.options.len() as f64) / self.capacity as f64).ceil() as usize
     }
 
+    /// The rows drawn besides the options, apart from the separators and group headers between
+    /// them
+    fn chrome_rows(&self) -> usize {
+        4 + self.description.lines().count().max(1)
+            + self.footer.is_some() as usize
+            + 2 * self.scroll_indicators as usize
+    }
+
+    fn fit_capacity(&mut self) {
+        let rows = term::fit_capacity(&self.term, self.chrome_rows());
+        let option_rows = match self.description_layout {
+            DescriptionLayout::Below => option::rows_below(&self.options),
+            DescriptionLayout::Side => 1,
+        };
+        // separators and group headers take rows too, but only the ones drawn on the page
+        self.capacity = (1..=rows)
+            .rev()
+            .find(|&capacity| {
+                let extra = self.options.chunks(capacity).map(|page| {
+                    let separators = page.iter().filter(|o| o.separator.is_some()).count();
+                    separators + Self::group_headers(page)
+                });
+                capacity * option_rows + extra.max().unwrap_or(0) <= rows
+            })
+            .unwrap_or(1);
+    }
+
+    /// Marks the remembered option as the initially selected one, if it is still offered
+    fn restore_remembered(&mut self) {
+        let Some(path) = &self.remember else {
+            return;
+        };
+        let Some(Remembered::One(label)) = remember::load(path, &self.title) else {
+            return;
+        };
+        if self.options.iter().any(|o| o.label == label) {
+            for option in &mut self.options {
+                option.selected = option.label == label;
+            }
+        }
+    }
+
     fn get_selected_option_idx(&mut self) -> usize {
         self.visible_options()
             .iter()
@@ -324,7 +1189,46 @@ impl<'a, T> Select<'a, T> {
             .unwrap_or(0)
     }
 
+    /// The number of group header rows drawn for a page of `options`
+    fn group_headers(options: &[DemandOption<T>]) -> usize {
+        let mut headers = 0;
+        let mut group = None;
+        for option in options {
+            let prev = std::mem::replace(&mut group, option.group.as_ref());
+            headers += (group.is_some() && group != prev) as usize;
+        }
+        headers
+    }
+
+    /// The extra columns an option's label is indented by to set it below its group header
+    fn group_indent(option: &DemandOption<T>) -> usize {
+        match option.group {
+            Some(_) => 2,
+            None => 0,
+        }
+    }
+
+    /// The label as drawn, truncated to the width left after the cursor if truncation is on
+    fn display_label<'o>(&self, option: &'o DemandOption<T>, cursor_width: usize) -> Cow<'o, str> {
+        match self.truncation {
+            Some(truncation) => {
+                let term_width = self.term.size().1 as usize;
+                let width = self.layout.max_width.unwrap_or(term_width).min(term_width);
+                truncate::truncate(
+                    &option.label,
+                    width.saturating_sub(cursor_width + 1),
+                    truncation,
+                )
+            }
+            None => Cow::Borrowed(&option.label),
+        }
+    }
+
     fn render(&self) -> io::Result<String> {
+        self.focused_row.set(None);
+        if self.showing_help {
+            return help::render_overlay(&self.theme, &self.title, &self.help_overlay_keys());
+        }
         let mut out = Buffer::ansi();
 
         out.set_color(&self.theme.title)?;
@@ -336,14 +1240,41 @@ impl<'a, T> Select<'a, T> {
             write!(out, "{}", self.description)?;
             writeln!(out)?;
         }
+        let cursor_width = console::measure_text_width(&self.theme.cursor_str);
+        let labels = self
+            .visible_options()
+            .iter()
+            .map(|o| self.display_label(o, cursor_width + Self::group_indent(o)))
+            .collect::<Vec<_>>();
         let max_label_len = self
             .visible_options()
             .iter()
-            .map(|o| console::measure_text_width(&o.label))
+            .zip(&labels)
+            .map(|(o, l)| {
+                Self::group_indent(o)
+                    + console::measure_text_width(l)
+                    + o.key_width()
+                    + o.badge_width()
+            })
             .max()
             .unwrap_or(0);
-        for (i, option) in self.visible_options().iter().enumerate() {
+        if self.scroll_indicators && self.cur_page > 0 {
+            out.set_color(&self.theme.description)?;
+            writeln!(out, "  {} more", theme::glyph("↑", "^"))?;
+        }
+        let mut group = None;
+        for (i, (option, label)) in self.visible_options().iter().zip(&labels).enumerate() {
+            if let Some(separator) = option.separator.as_ref().filter(|_| self.filter.is_empty()) {
+                out.set_color(&self.theme.description)?;
+                writeln!(out, "{} {}", " ".repeat(cursor_width), separator)?;
+            }
+            let prev_group = std::mem::replace(&mut group, option.group.as_ref());
+            if let Some(name) = option.group.as_ref().filter(|&g| prev_group != Some(g)) {
+                out.set_color(&self.theme.title)?;
+                writeln!(out, "{} {}", " ".repeat(cursor_width), name)?;
+            }
             if self.cursor_y == i {
+                self.focused_row.set(Some(term::row(&out)));
                 out.set_color(&self.theme.cursor)?;
                 write!(out, "{}", self.theme.cursor_str)?;
             } else {
@@ -353,24 +1284,54 @@ impl<'a, T> Select<'a, T> {
                     " ".repeat(console::measure_text_width(&self.theme.cursor_str))
                 )?;
             }
-            out.set_color(&self.theme.unselected_option)?;
+            let indent = Self::group_indent(option);
+            write!(out, "{}", " ".repeat(indent))?;
+            let label_color = match option.disabled {
+                true => &self.theme.description,
+                false => &self.theme.unselected_option,
+            };
+            out.set_color(label_color)?;
             if let Some(desc) = &option.description {
-                let label = console::pad_str(&option.label, max_label_len, Alignment::Left, None);
                 if self.filtering && !self.filter.is_empty() {
-                    self.highlight_matches(&mut out, &label)?;
+                    self.highlight_matches(&mut out, label)?;
                 } else {
+                    let label = term::hyperlink(label, option.link.as_deref());
                     write!(out, " {}", label)?;
                 }
-                out.set_color(&self.theme.description)?;
-                writeln!(out, "  {}", desc)?;
+                option::write_key(&mut out, option, &self.theme, label_color)?;
+                option::write_badge(&mut out, option, &self.theme, label_color)?;
+                let cursor_width = console::measure_text_width(&self.theme.cursor_str);
+                if self.description_layout == DescriptionLayout::Below {
+                    out.set_color(&self.theme.description)?;
+                    option::write_description_below(&mut out, desc, cursor_width + 1 + indent)?;
+                } else {
+                    let width = indent
+                        + console::measure_text_width(label)
+                        + option.key_width()
+                        + option.badge_width();
+                    write!(out, "{}", " ".repeat(max_label_len - width))?;
+                    out.set_color(&self.theme.description)?;
+                    let indent = cursor_width + 1 + max_label_len;
+                    option::write_description(&mut out, desc, indent)?;
+                }
             } else if self.filtering && !self.filter.is_empty() {
-                self.highlight_matches(&mut out, &option.label)?;
+                self.highlight_matches(&mut out, label)?;
+                option::write_key(&mut out, option, &self.theme, label_color)?;
+                option::write_badge(&mut out, option, &self.theme, label_color)?;
                 writeln!(out)?;
             } else {
-                writeln!(out, " {}", option.label)?;
+                let label = term::hyperlink(label, option.link.as_deref());
+                write!(out, " {}", label)?;
+                option::write_key(&mut out, option, &self.theme, label_color)?;
+                option::write_badge(&mut out, option, &self.theme, label_color)?;
+                writeln!(out)?;
             }
         }
 
+        if self.scroll_indicators && self.cur_page + 1 < self.pages {
+            out.set_color(&self.theme.description)?;
+            writeln!(out, "  {} more", theme::glyph("↓", "v"))?;
+        }
         if !self.filtering && self.pages > 1 {
             out.set_color(&self.theme.description)?;
             writeln!(out, " (page {}/{})", self.cur_page + 1, self.pages)?;
@@ -407,37 +1368,69 @@ impl<'a, T> Select<'a, T> {
 
         writeln!(out)?;
         out.reset()?;
+        help::render_footer(&mut out, &self.theme, &self.footer)?;
 
         Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
     }
 
+    /// The key shown for clearing the filter while filtering
+    fn clear_filter_key(&self) -> &'static str {
+        match self.escape_clears_filter {
+            true => "esc",
+            false => "ctrl+u",
+        }
+    }
+
     fn print_help_keys(&self, out: &mut Buffer) -> io::Result<()> {
-        let mut help_keys = vec![("↑/↓/k/j", "up/down")];
+        let [up_down, prev_next] = nav_keys(self.vim_keys);
+        let mut help_keys = vec![(up_down, "up/down")];
         if self.pages > 1 {
-            help_keys.push(("←/→/h/l", "prev/next page"));
+            help_keys.push((prev_next, "prev/next page"));
         }
         if self.filterable {
             if self.filtering {
-                help_keys = vec![("esc", "clear filter")];
+                help_keys = vec![(self.clear_filter_key(), "clear filter")];
             } else {
                 help_keys.push(("/", "filter"));
-                if !self.filter.is_empty() {
+                if !self.filter.is_empty() && self.escape_clears_filter {
                     help_keys.push(("esc", "clear filter"));
                 }
             }
         }
+        if self.help_overlay && !self.filtering {
+            help_keys.push(("?", "help"));
+        }
         help_keys.push(("enter", "confirm"));
-        for (i, (key, desc)) in help_keys.iter().enumerate() {
-            if i > 0 || (!self.filtering && !self.filter.is_empty()) {
-                out.set_color(&self.theme.help_sep)?;
-                write!(out, " • ")?;
-            }
-            out.set_color(&self.theme.help_key)?;
-            write!(out, "{}", key)?;
-            out.set_color(&self.theme.help_desc)?;
-            write!(out, " {}", desc)?;
+        if self.back_hint && !self.filtering && self.filter.is_empty() {
+            help_keys.push(("esc", "back"));
         }
-        Ok(())
+        help::render_keys(
+            out,
+            &self.theme,
+            &help_keys,
+            !self.filtering && !self.filter.is_empty(),
+            0,
+            self.term.size().1 as usize,
+        )
+    }
+
+    fn help_overlay_keys(&self) -> Vec<(&'static str, &'static str)> {
+        let [up_down, prev_next] = nav_keys(self.vim_keys);
+        let mut keys = vec![(up_down, "up/down"), (prev_next, "prev/next page")];
+        if self.vim_keys == Some(true) {
+            keys.extend(VIM_KEYS);
+        }
+        if self.filterable {
+            keys.push(("/", "filter"));
+            keys.push(("ctrl+u", "clear filter while typing"));
+        }
+        if self.filterable && self.escape_clears_filter {
+            keys.push(("esc", "clear filter or cancel"));
+        } else {
+            keys.push(("esc", "cancel"));
+        }
+        keys.push(("enter", "confirm"));
+        keys
     }
 
     fn get_char_idx(&self, input: &str, cursor: usize) -> usize {
@@ -474,7 +1467,22 @@ impl<'a, T> Select<'a, T> {
         Ok(())
     }
 
+    /// The text shown for `option` on the line shown after submitting
+    fn success_text(&self, option: &DemandOption<T>) -> String {
+        match self.success_item {
+            Some(text) => text(&option.item),
+            None => option.label.clone(),
+        }
+    }
+
     fn render_success(&self, selected: &str) -> io::Result<String> {
+        if let Some(format) = &self.success_format {
+            let outcome = Outcome {
+                title: &self.title,
+                values: vec![selected],
+            };
+            return Ok(outcome::render(format, &outcome));
+        }
         let mut out = Buffer::ansi();
         out.set_color(&self.theme.title)?;
         write!(out, "{}", self.title)?;
@@ -485,12 +1493,64 @@ impl<'a, T> Select<'a, T> {
     }
 
     fn clear(&mut self) -> io::Result<()> {
-        self.term.clear_last_lines(self.height)?;
+        if self.fixed_position {
+            term::restore_cursor(&self.term)?;
+            self.term.clear_to_end_of_screen()?;
+        } else {
+            self.term.clear_last_lines(self.height)?;
+        }
         self.height = 0;
         Ok(())
     }
 }
 
+impl<T: Display> Select<'_, T> {
+    /// Set whether the line shown after submitting displays the option labels (the default) or
+    /// the items themselves
+    pub fn success_shows(mut self, shows: SuccessShows) -> Self {
+        self.success_item = match shows {
+            SuccessShows::Label => None,
+            SuccessShows::Item => Some(T::to_string),
+        };
+        self
+    }
+}
+
+impl<T: Clone> Select<'_, T> {
+    /// Displays the selector like `run` without consuming it, so it can be shown again
+    ///
+    /// The cursor, page and filter are reset on each run, while the options and settings are
+    /// kept. Options returned by `on_filter_change` are only shown until the next run.
+    pub fn run_mut(&mut self) -> io::Result<T> {
+        let (id, _) = self.run_in_place()?;
+        Ok(self
+            .options
+            .iter()
+            .find(|o| o.id == id)
+            .unwrap()
+            .item
+            .clone())
+    }
+}
+
+impl<T: PartialEq> Select<'_, T> {
+    /// Displays the selector like `run` and returns whether the chosen item equals `item`
+    pub fn run_is(self, item: &T) -> io::Result<bool> {
+        self.run().map(|chosen| chosen == *item)
+    }
+}
+
+/// Help keys shown for the extra bindings enabled with `vim_keys(true)`
+pub(crate) const VIM_KEYS: [(&str, &str); 2] = [("g/G", "first/last"), ("ctrl+d/u", "half page")];
+
+/// The up/down and prev/next page help keys for a `vim_keys` setting
+pub(crate) fn nav_keys(vim_keys: Option<bool>) -> [&'static str; 2] {
+    match vim_keys {
+        Some(false) => ["↑/↓", "←/→"],
+        _ => ["↑/↓/k/j", "←/→/h/l"],
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::without_ansi;
@@ -524,6 +1584,301 @@ mod tests {
         );
     }
 
+    #[test]
+    fn options_mut() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany").selected(true))
+            .option(DemandOption::new("Brazil"));
+        for option in select.options_mut() {
+            option.selected = option.label == "Brazil";
+        }
+        select.options_mut()[0].label = "Deutschland".to_string();
+        select.cursor_y = select.get_selected_option_idx();
+
+        assert_eq!(
+            indoc! {
+              "Country
+              Deutschland
+            ❯ Brazil
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn options_from() {
+        let select = Select::new("Country").options_from([("Germany", "de"), ("Brazil", "br")]);
+
+        assert_eq!(
+            vec!["de", "br"],
+            select.options.iter().map(|o| o.item).collect::<Vec<_>>()
+        );
+        assert_ne!(select.options[0].id, select.options[1].id);
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany
+              Brazil
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn keep_focus_on_clear() {
+        let mut select = Select::new("Country")
+            .filterable(true)
+            .keep_focus_on_clear(true)
+            .option(DemandOption::new("United States"))
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Brazil"));
+        select.handle_start_filtering();
+        select.filter = "br".to_string();
+        select.handle_stop_filtering(false).unwrap();
+
+        assert_eq!(2, select.cursor_y);
+        assert!(select.filter.is_empty());
+    }
+
+    #[test]
+    fn stable_filter() {
+        let mut select = Select::new("Country")
+            .stable_filter(true)
+            .option(DemandOption::new("Japan"))
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Angola"));
+        select.filter = "an".to_string();
+
+        let labels = select
+            .filtered_options()
+            .iter()
+            .map(|o| o.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Japan", "Germany", "Angola"], labels);
+    }
+
+    #[test]
+    fn scroll_indicators() {
+        let mut select = Select::new("Country")
+            .scroll_indicators(true)
+            .option(DemandOption::new("Japan"))
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Angola"))
+            .option(DemandOption::new("Brazil"))
+            .option(DemandOption::new("Chile"));
+        select.capacity = 2;
+        select.pages = select.get_pages();
+        select.cur_page = 1;
+
+        assert_eq!(
+            indoc! {
+              "Country
+
+              ↑ more
+            ❯ Angola
+              Brazil
+              ↓ more
+             (page 2/3)
+            ↑/↓/k/j up/down • ←/→/h/l prev/next page • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn filter_ctrl_u() {
+        let mut select = Select::new("Country")
+            .filterable(true)
+            .option(DemandOption::new("Germany"));
+        select.handle_start_filtering();
+        select.filter = "gerx".to_string();
+        select.cursor_x = 3;
+        select.handle_filter_ctrl_u().unwrap();
+
+        assert_eq!("x", select.filter);
+        assert_eq!(0, select.cursor_x);
+        assert!(select.filtering);
+    }
+
+    #[test]
+    fn on_filter_change() {
+        let mut select = Select::new("Package")
+            .filterable(true)
+            .on_filter_change(|query| {
+                Some(vec![
+                    DemandOption::new(format!("{query}-cli")),
+                    DemandOption::new(format!("lib{query}")),
+                ])
+            })
+            .option(DemandOption::new("serde".to_string()));
+        select.handle_start_filtering();
+        select.handle_filter_key('x').unwrap();
+
+        let labels = select
+            .filtered_options()
+            .iter()
+            .map(|o| o.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["x-cli", "libx"], labels);
+    }
+
+    #[test]
+    fn separator() {
+        let select = Select::new("Action")
+            .option(DemandOption::new("Open"))
+            .option(DemandOption::new("Save"))
+            .separator()
+            .option(DemandOption::new("Quit"));
+
+        assert_eq!(
+            indoc! {
+              "Action
+            ❯ Open
+              Save
+              ───
+              Quit
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn option_key() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany").key('g'))
+            .option(DemandOption::new("Brazil").key('b'));
+
+        assert!(select.handle_option_key('b'));
+        assert_eq!(1, select.cursor_y);
+        assert!(!select.handle_option_key('x'));
+        assert_eq!(1, select.cursor_y);
+    }
+
+    #[test]
+    fn option_key_shown() {
+        let select = Select::new("Country")
+            .option(DemandOption::new("Germany").key('g'))
+            .option(DemandOption::new("Brazil"));
+
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany [g]
+              Brazil
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn status() {
+        let select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .status(|| "1 result".to_string());
+
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany
+            ↑/↓/k/j up/down • enter confirm
+            1 result
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn on_key() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .on_key(|key, options| match key {
+                Key::Char('r') => {
+                    options.push(DemandOption::new("Brazil"));
+                    KeyOutcome::Rerender
+                }
+                _ => KeyOutcome::Ignored,
+            });
+        select.handle_on_key(Key::Char('r'));
+        select.handle_on_key(Key::Char('q'));
+
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany
+              Brazil
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn stream() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut select = Select::new("Country").stream(receiver);
+        sender.send(DemandOption::new("Germany")).unwrap();
+        sender.send(DemandOption::new("Brazil")).unwrap();
+        drop(sender);
+
+        assert!(select.read_key().unwrap().is_none());
+        assert!(select.stream.is_none());
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany
+              Brazil
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn help_overlay() {
+        let mut select = Select::new("Country")
+            .filterable(true)
+            .enable_help_overlay(true)
+            .option(DemandOption::new("Germany"));
+
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany
+            ↑/↓/k/j up/down • / filter • ? help • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+
+        select.showing_help = true;
+        assert_eq!(
+            indoc! {
+              "Country
+                ↑/↓/k/j  up/down
+                ←/→/h/l  prev/next page
+                /        filter
+                ctrl+u   clear filter while typing
+                esc      clear filter or cancel
+                enter    confirm
+              press any key to close
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
     #[test]
     fn non_display() {
         struct Thing {
@@ -565,4 +1920,427 @@ mod tests {
             without_ansi(select.render().unwrap().as_str())
         );
     }
+
+    #[test]
+    fn success_format() {
+        let select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .success_format(|o| format!("✔ {}: {}", o.title, o.value()));
+        assert_eq!(
+            "✔ Country: Germany\n",
+            without_ansi(select.render_success("Germany").unwrap().as_str())
+        );
+
+        let select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .success_format(|_| String::new());
+        assert_eq!("", select.render_success("Germany").unwrap());
+    }
+
+    #[test]
+    fn back_hint() {
+        let select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .back_hint(true);
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany
+            ↑/↓/k/j up/down • enter confirm • esc back
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn highlighted() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Japan"));
+        assert_eq!("Germany", select.highlighted().unwrap().label);
+        select.handle_down().unwrap();
+        assert_eq!("Japan", select.highlighted().unwrap().label);
+        select.filter = "xyz".to_string();
+        assert!(select.highlighted().is_none());
+    }
+
+    #[test]
+    fn badge() {
+        let select = Select::new("Editor")
+            .option(
+                DemandOption::new("Helix")
+                    .badge("new")
+                    .description("A modal editor"),
+            )
+            .option(DemandOption::new("Vim").description("The classic"));
+        assert_eq!(
+            indoc! {
+              "Editor
+            ❯ Helix new  A modal editor
+              Vim        The classic
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn escape_clears_filter() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .filterable(true)
+            .escape_clears_filter(false);
+        select.filtering = true;
+        select.filter = "ger".to_string();
+        assert_eq!(
+            indoc! {
+              "Country
+            ❯ Germany
+            /ger
+            ctrl+u clear filter • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn describe_with() {
+        let descriptions = std::collections::HashMap::from([("Germany", "Berlin")]);
+        let select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Brazil").description("Brasília"))
+            .describe_with(|o| descriptions.get(o.label.as_str()).map(|d| d.to_string()));
+        let descriptions = select
+            .options
+            .iter()
+            .map(|o| o.description.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some("Berlin"), Some("Brasília")], descriptions);
+    }
+
+    #[test]
+    fn focused_row() {
+        let mut select = Select::new("Country")
+            .description("Pick a country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Brazil"))
+            .show_system_cursor(true);
+        select.cursor_y = 1;
+        let output = select.render().unwrap();
+        assert_eq!(Some(3), select.focused_row.get());
+        assert_eq!(Some("❯ Brazil"), without_ansi(&output).lines().nth(3));
+    }
+
+    #[test]
+    fn sort_by() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Brazil"))
+            .option(DemandOption::new("Angola"))
+            .sort_by(|a, b| a.label.cmp(&b.label));
+        select.sort_options();
+        let labels = select
+            .visible_options()
+            .iter()
+            .map(|o| o.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Angola", "Brazil", "Germany"], labels);
+    }
+
+    #[test]
+    fn run_non_tty() {
+        let select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Brazil"));
+        let (item, output) = select.run_non_tty("bra\n").unwrap();
+        assert_eq!("Brazil", item);
+        assert_eq!("Country\n  1. Germany\n  2. Brazil\n> ", output);
+    }
+
+    #[test]
+    fn insertion_index() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Brazil"))
+            .sort_by(|a, b| a.label.cmp(&b.label));
+        let germany = select.options[0].id;
+        select.prepare();
+        select.push_option(DemandOption::new("Angola"));
+        let angola = select.options[2].id;
+        select.sort_options();
+        assert_eq!(0, select.insertion_index(germany));
+        assert_eq!(2, select.insertion_index(angola));
+        assert_eq!("Angola", select.options[0].label);
+    }
+
+    #[test]
+    fn insertion_index_after_filter_change() {
+        let mut select = Select::new("Country")
+            .filterable(true)
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Brazil"))
+            .option(DemandOption::new("Angola"))
+            .sort_by(|a, b| a.label.cmp(&b.label))
+            .on_filter_change(|_| {
+                Some(vec![DemandOption::new("Chile"), DemandOption::new("Peru")])
+            });
+        select.prepare();
+        select.handle_start_filtering();
+        select.handle_filter_key('x').unwrap();
+        select.sort_options();
+
+        let peru = select.options[1].id;
+        assert_eq!(1, select.insertion_index(peru));
+    }
+
+    #[test]
+    fn description_below() {
+        let select = Select::new("Editor")
+            .description_layout(DescriptionLayout::Below)
+            .option(DemandOption::new("Helix").description("A modal editor"))
+            .option(DemandOption::new("Vim").description("The classic\nSince 1991"));
+        assert_eq!(
+            indoc! {
+              "Editor
+            ❯ Helix
+              A modal editor
+              Vim
+              The classic
+              Since 1991
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+        assert_eq!(3, option::rows_below(&select.options));
+    }
+
+    #[test]
+    fn vim_keys() {
+        let mut select = Select::new("Number")
+            .options((1..=10).map(DemandOption::new).collect())
+            .vim_keys(true);
+        select.capacity = 4;
+        select.pages = select.get_pages();
+        select.handle_last().unwrap();
+        assert_eq!((2, 1), (select.cur_page, select.cursor_y));
+        assert_eq!(10, select.highlighted().unwrap().item);
+        select.handle_first().unwrap();
+        assert_eq!((0, 0), (select.cur_page, select.cursor_y));
+        select.handle_half_page(true).unwrap();
+        select.handle_half_page(true).unwrap();
+        assert_eq!(5, select.highlighted().unwrap().item);
+        assert_eq!(4, select.metrics.navigations);
+
+        let select = select.vim_keys(false);
+        assert_eq!(["↑/↓", "←/→"], nav_keys(select.vim_keys));
+    }
+
+    #[test]
+    fn disabled() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Japan").disabled(true))
+            .option(DemandOption::new("Peru"));
+        select.handle_down().unwrap();
+        assert_eq!("Peru", select.highlighted().unwrap().label);
+        select.handle_up().unwrap();
+        assert_eq!("Germany", select.highlighted().unwrap().label);
+        select.cursor_y = 1;
+        assert!(!select.can_submit());
+    }
+
+    #[test]
+    fn success_shows() {
+        let select = Select::new("Port").option(DemandOption::with_label("HTTPS", 443));
+        assert_eq!("HTTPS", select.success_text(&select.options[0]));
+        let select = select.success_shows(SuccessShows::Item);
+        assert_eq!("443", select.success_text(&select.options[0]));
+    }
+
+    #[test]
+    fn reset() {
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Japan"))
+            .filterable(true);
+        select.filtering = true;
+        select.filter = "jap".to_string();
+        select.cursor_x = 3;
+        select.handle_down().unwrap();
+        select.reset();
+        assert_eq!(
+            (0, 0, 0),
+            (select.cursor_x, select.cursor_y, select.cur_page)
+        );
+        assert!(!select.filtering && select.filter.is_empty());
+        assert_eq!(0, select.metrics.navigations);
+        assert_eq!(2, select.options.len());
+
+        // a selector built to start filtering does so on every run
+        let mut select = select.filtering(true);
+        select.reset();
+        assert!(select.filtering);
+        select.handle_stop_filtering(false).unwrap();
+        select.reset();
+        assert!(select.filtering);
+    }
+
+    #[test]
+    fn run_twice() {
+        let mut select = Select::new("Package")
+            .filterable(true)
+            .option(DemandOption::new("serde".to_string()))
+            .option(DemandOption::new("tokio".to_string()))
+            .on_filter_change(|query| Some(vec![DemandOption::new(format!("{query}-cli"))]));
+        let run = |select: &mut Select<String>, input: &str| {
+            select.reset();
+            select.prepare();
+            let mut out = vec![];
+            let (id, _) = select
+                .read_non_tty(Instant::now(), &mut input.as_bytes(), &mut out)
+                .unwrap();
+            let option = select.options.iter().find(|o| o.id == id).unwrap();
+            option.label.clone()
+        };
+
+        assert_eq!("tokio", run(&mut select, "2\n"));
+        select.handle_start_filtering();
+        select.handle_filter_key('x').unwrap();
+        assert_eq!("x-cli", select.options[0].label);
+        select.filtering = false;
+        assert_eq!("tokio", run(&mut select, "2\n"));
+        assert!(!select.filter_replaced);
+    }
+
+    #[test]
+    fn group() {
+        let mut select = Select::new("Food")
+            .group(
+                "Fruit",
+                vec![DemandOption::new("Apple"), DemandOption::new("Banana")],
+            )
+            .group("Vegetable", vec![DemandOption::new("Carrot")]);
+        assert_eq!(
+            indoc! {
+              "Food
+              Fruit
+            ❯   Apple
+                Banana
+              Vegetable
+                Carrot
+            ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+        assert_eq!(2, Select::group_headers(&select.options));
+        select.handle_down().unwrap();
+        select.handle_down().unwrap();
+        assert_eq!("Carrot", select.highlighted().unwrap().label);
+
+        select.filter = "car".to_string();
+        select.cursor_y = 0;
+        assert_eq!(
+            indoc! {
+              "Food
+              Vegetable
+            ❯   Carrot
+             • ↑/↓/k/j up/down • enter confirm
+            "
+            },
+            without_ansi(select.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn group_headers_per_page() {
+        let mut select = Select::new("Number");
+        for group in 0..10 {
+            let options = (0..10).map(|i| DemandOption::new(group * 10 + i)).collect();
+            select = select.group(&format!("Group {group}"), options);
+        }
+        select.fit_capacity();
+        // the rows left for options and headers beside the title, description and help lines
+        let rows = select.term.size().0 as usize - 6;
+        let headers = select
+            .options
+            .chunks(select.capacity)
+            .map(Select::group_headers);
+        assert!(select.capacity + headers.max().unwrap() <= rows);
+        // reserving rows for all ten headers would leave fewer
+        assert!(select.capacity > rows - 10);
+    }
+
+    #[test]
+    fn separators_per_page() {
+        let mut select = Select::new("Number");
+        for i in 0..100 {
+            select = select.option(DemandOption::new(i)).separator();
+        }
+        select.fit_capacity();
+        // only the options on a page draw their separators
+        let rows = select.term.size().0 as usize - 6;
+        assert_eq!(rows / 2, select.capacity);
+    }
+
+    #[test]
+    fn filter_skips_disabled() {
+        let mut select = Select::new("Fruit")
+            .filterable(true)
+            .option(DemandOption::new("Apple").disabled(true))
+            .option(DemandOption::new("Apricot"));
+        select.handle_start_filtering();
+        select.handle_filter_key('a').unwrap();
+        assert_eq!(1, select.cursor_y);
+    }
+
+    #[test]
+    fn observe() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut select = Select::new("Country")
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Japan"))
+            .observe(tx);
+        let mut out = vec![];
+        select
+            .read_non_tty(Instant::now(), &mut "2\n".as_bytes(), &mut out)
+            .unwrap();
+        select.cancel::<()>().unwrap_err();
+
+        let events = rx.try_iter().collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                PromptEvent::Submitted {
+                    title: "Country".to_string(),
+                    value: "Japan".to_string(),
+                },
+                PromptEvent::Cancelled {
+                    title: "Country".to_string(),
+                },
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn metrics() {
+        let mut select = Select::new("Country")
+            .filterable(true)
+            .option(DemandOption::new("Germany"))
+            .option(DemandOption::new("Japan"))
+            .option(DemandOption::new("Brazil"));
+        select.handle_down().unwrap();
+        select.handle_down().unwrap();
+        select.handle_up().unwrap();
+        select.handle_start_filtering();
+        select.handle_stop_filtering(false).unwrap();
+
+        let (_, metrics) = select.submit(Instant::now()).unwrap();
+        assert_eq!(3, metrics.navigations);
+        assert!(metrics.filtered);
+    }
 }
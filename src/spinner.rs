@@ -1,4 +1,6 @@
 use std::{
+    borrow::Cow,
+    collections::VecDeque,
     io::{self, Write},
     marker::PhantomData,
     sync::mpsc::{self, Sender, TryRecvError},
@@ -10,7 +12,7 @@ use console::Term;
 use once_cell::sync::Lazy;
 use termcolor::{Buffer, WriteColor};
 
-use crate::{ctrlc, theme, Theme};
+use crate::{ctrlc, term, theme, Theme};
 
 /// tell a prompt to do something while running
 /// currently its only useful for spinner
@@ -22,6 +24,8 @@ pub enum SpinnerAction {
     Style(&'static SpinnerStyle),
     /// change the title
     Title(String),
+    /// add a line to the log shown under the title
+    Log(String),
 }
 
 // SAFETY: ensure that 'spinner lives longer than any use of style or theme by spinner
@@ -65,6 +69,15 @@ impl<'spinner> SpinnerActionRunner<'spinner> {
     ) -> Result<(), std::sync::mpsc::SendError<SpinnerAction>> {
         self.sender.send(SpinnerAction::Title(title.into()))
     }
+
+    /// add a line under the spinner title
+    /// only the most recent lines are kept, see `Spinner::log_lines`
+    pub fn log<S: Into<String>>(
+        &self,
+        line: S,
+    ) -> Result<(), std::sync::mpsc::SendError<SpinnerAction>> {
+        self.sender.send(SpinnerAction::Log(line.into()))
+    }
 }
 
 /// Show a spinner
@@ -88,11 +101,15 @@ pub struct Spinner<'a> {
     // The style of the spinner
     pub style: &'a SpinnerStyle,
     /// The colors/style of the spinner
-    pub theme: &'a Theme,
+    pub theme: Cow<'a, Theme>,
 
     term: Term,
     frame: usize,
     height: usize,
+    logs: VecDeque<String>,
+    log_lines: usize,
+    inline: bool,
+    width: usize,
 }
 
 impl<'a> Spinner<'a> {
@@ -101,10 +118,14 @@ impl<'a> Spinner<'a> {
         Self {
             title: title.into(),
             style: &DEFAULT,
-            theme: &theme::DEFAULT,
-            term: Term::stderr(),
+            theme: Cow::Owned(theme::default_theme()),
+            term: term::default_term(),
             frame: 0,
             height: 0,
+            logs: VecDeque::new(),
+            log_lines: 3,
+            inline: false,
+            width: 0,
         }
     }
 
@@ -114,9 +135,33 @@ impl<'a> Spinner<'a> {
         self
     }
 
+    /// Set how many of the most recent lines sent with `SpinnerActionRunner::log` are shown
+    /// under the title. Defaults to 3.
+    pub fn log_lines(mut self, log_lines: usize) -> Self {
+        self.log_lines = log_lines;
+        self
+    }
+
+    /// Set whether the spinner is drawn after text already on the cursor's line instead of on
+    /// a line of its own
+    ///
+    /// Only the title, if any, and the frame after it are redrawn, so the spinner can follow text
+    /// printed beforehand, e.g. `Building project... ⣾`. Log lines are not shown.
+    pub fn inline(mut self, inline: bool) -> Self {
+        self.inline = inline;
+        self
+    }
+
+    /// Set the terminal the spinner is drawn on, e.g. `Term::stdout()` to keep it apart from
+    /// logging on stderr. Defaults to the terminal set with `set_term`.
+    pub fn term(mut self, term: Term) -> Self {
+        self.term = term;
+        self
+    }
+
     /// Set the theme of the dialog
-    pub fn theme(mut self, theme: &'a Theme) -> Self {
-        self.theme = theme;
+    pub fn theme<S: Into<Cow<'a, Theme>>>(mut self, theme: S) -> Self {
+        self.theme = theme.into();
         self
     }
 
@@ -148,7 +193,8 @@ impl<'a> Spinner<'a> {
                     Ok(a) => match a {
                         SpinnerAction::Title(title) => self.title = title,
                         SpinnerAction::Style(s) => self.style = s,
-                        SpinnerAction::Theme(theme) => self.theme = theme,
+                        SpinnerAction::Theme(theme) => self.theme = Cow::Borrowed(theme),
+                        SpinnerAction::Log(line) => self.push_log(line),
                     },
                     Err(TryRecvError::Empty) => (),
                     Err(TryRecvError::Disconnected) => {
@@ -160,7 +206,9 @@ impl<'a> Spinner<'a> {
                 self.clear()?;
                 let output = self.render()?;
                 self.height = output.lines().count() - 1;
+                self.width = console::measure_text_width(&output);
                 self.term.write_all(output.as_bytes())?;
+                self.term.flush()?;
                 sleep(self.style.fps);
                 if handle.is_finished() {
                     self.clear()?;
@@ -168,9 +216,9 @@ impl<'a> Spinner<'a> {
                     break;
                 }
             }
-            handle.join().map_err(|e| {
-                io::Error::new(io::ErrorKind::Other, format!("thread panicked: {e:?}"))
-            })
+            handle
+                .join()
+                .map_err(|e| io::Error::other(format!("thread panicked: {e:?}")))
         })
     }
 
@@ -182,21 +230,51 @@ impl<'a> Spinner<'a> {
             self.frame = 0
         }
 
+        if self.inline {
+            if !self.title.is_empty() {
+                write!(out, "{} ", self.title)?;
+            }
+            out.set_color(&self.theme.input_prompt)?;
+            write!(out, "{}", self.style.frames[self.frame])?;
+            out.reset()?;
+            self.frame += 1;
+            return Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string());
+        }
+
         out.set_color(&self.theme.input_prompt)?;
         write!(out, "{} ", self.style.frames[self.frame])?;
         out.reset()?;
 
         write!(out, "{}", self.title)?;
 
+        out.set_color(&self.theme.description)?;
+        for line in &self.logs {
+            write!(out, "\n  {}", line)?;
+        }
+        out.reset()?;
+
         self.frame += 1;
 
         Ok(std::str::from_utf8(out.as_slice()).unwrap().to_string())
     }
 
+    fn push_log(&mut self, line: String) {
+        self.logs.push_back(line);
+        while self.logs.len() > self.log_lines {
+            self.logs.pop_front();
+        }
+    }
+
     fn clear(&mut self) -> io::Result<()> {
-        if self.height == 0 {
-            self.term.clear_line()?;
-        } else {
+        if self.inline {
+            // erase only what the spinner drew, keeping the text before it
+            self.term.move_cursor_left(self.width)?;
+            self.term.write_str("\x1b[K")?;
+            self.width = 0;
+            return Ok(());
+        }
+        self.term.clear_line()?;
+        if self.height > 0 {
             self.term.clear_last_lines(self.height)?;
         }
         self.height = 0;
@@ -288,7 +366,7 @@ mod test {
 
     #[test]
     fn test_render() {
-        for t in vec![
+        for t in [
             SpinnerStyle::dots(),
             SpinnerStyle::jump(),
             SpinnerStyle::line(),
@@ -307,6 +385,32 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_render_logs() {
+        let style = SpinnerStyle::line();
+        let mut spinner = Spinner::new("Building").style(&style).log_lines(2);
+        for line in ["Compiling a", "Compiling b", "Compiling c"] {
+            spinner.push_log(line.to_string());
+        }
+        assert_eq!(
+            "- Building\n  Compiling b\n  Compiling c",
+            without_ansi(spinner.render().unwrap().as_str())
+        );
+    }
+
+    #[test]
+    fn test_render_inline() {
+        let style = SpinnerStyle::line();
+        let mut spinner = Spinner::new("").style(&style).inline(true);
+        spinner.push_log("Compiling a".to_string());
+        assert_eq!("-", without_ansi(spinner.render().unwrap().as_str()));
+        spinner.title = "Building project...".to_string();
+        assert_eq!(
+            "Building project... \\",
+            without_ansi(spinner.render().unwrap().as_str())
+        );
+    }
+
     #[test]
     fn scope_test() {
         let spinner = Spinner::new("Scoped");
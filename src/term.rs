@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use console::Term;
+use termcolor::Buffer;
+
+static TERM: RwLock<Option<Term>> = RwLock::new(None);
+
+/// Set the terminal used by all prompts created afterwards.
+///
+/// By default prompts write to stderr. This can route them to a buffered terminal in tests or to
+/// a pty.
+///
+/// # Example
+///
+/// ```
+/// use console::Term;
+///
+/// demand::set_term(Term::buffered_stderr());
+/// ```
+pub fn set_term(term: Term) {
+    *TERM.write().unwrap() = Some(term);
+}
+
+/// Whether prompts created now will run interactively
+///
+/// When the terminal set with `set_term` (stderr by default) is not a TTY, `Select` and
+/// `MultiSelect` list their options and `Confirm` prints its title, and they read the answer
+/// from stdin instead.
+///
+/// # Example
+///
+/// ```
+/// if demand::is_interactive() {
+///     eprintln!("Use the arrow keys to pick a country");
+/// }
+/// ```
+pub fn is_interactive() -> bool {
+    default_term().is_term()
+}
+
+/// The terminal prompts use when none is set explicitly
+pub(crate) fn default_term() -> Term {
+    TERM.read().unwrap().clone().unwrap_or_else(Term::stderr)
+}
+
+/// The number of option lines that fit in the terminal alongside `chrome` other lines
+///
+/// Prompts pass the rows their title, description, paging, filter, help and footer lines take up,
+/// and shrink their page size to the result so the whole prompt stays on screen. At least one
+/// option always fits.
+pub(crate) fn fit_capacity(term: &Term, chrome: usize) -> usize {
+    // one extra row for the line the cursor rests on after rendering
+    (term.size().0 as usize).saturating_sub(chrome + 1).max(1)
+}
+
+/// Save the cursor position with DECSC so `restore_cursor` can return to it
+pub(crate) fn save_cursor(term: &Term) -> io::Result<()> {
+    term.write_str("\x1b7")
+}
+
+/// Return to the cursor position saved by `save_cursor` with DECRC
+pub(crate) fn restore_cursor(term: &Term) -> io::Result<()> {
+    term.write_str("\x1b8")
+}
+
+/// The number of lines written to `out` so far, i.e. the row the next write starts on
+pub(crate) fn row(out: &Buffer) -> usize {
+    out.as_slice().iter().filter(|&&b| b == b'\n').count()
+}
+
+/// Move the cursor from just below `output` up to the start of its line `row`, returning how many
+/// lines it moved
+pub(crate) fn lift_cursor(term: &Term, output: &str, row: usize) -> io::Result<usize> {
+    // the cursor rests on the row after the last newline, even if escapes follow it
+    let lift = output.matches('\n').count().saturating_sub(row);
+    term.move_cursor_up(lift)?;
+    Ok(lift)
+}
+
+/// Wrap `text` in an OSC 8 hyperlink to `link`
+///
+/// Terminals without hyperlink support ignore the sequence, but it is left out when colors are
+/// disabled so piped or plain output stays free of escapes.
+pub(crate) fn hyperlink<'t>(text: &'t str, link: Option<&str>) -> Cow<'t, str> {
+    match link {
+        Some(link) if console::colors_enabled_stderr() => Cow::Owned(osc8(text, link)),
+        _ => Cow::Borrowed(text),
+    }
+}
+
+/// The OSC 8 escape sequence showing `text` as a hyperlink to `link`
+fn osc8(text: &str, link: &str) -> String {
+    format!("\x1b]8;;{link}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+static LAST_HEIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of terminal rows the most recently finished prompt left on screen.
+///
+/// Lines wider than the terminal are counted once per row they wrap onto.
+pub fn last_height() -> usize {
+    LAST_HEIGHT.load(Ordering::Relaxed)
+}
+
+/// Record the final output of a prompt for `last_height`
+pub(crate) fn record_height(term: &Term, output: &str) {
+    LAST_HEIGHT.store(
+        wrapped_height(term.size().1 as usize, output),
+        Ordering::Relaxed,
+    );
+}
+
+/// Mirror the final output of a prompt to `sink` without colors, ignoring write errors
+pub(crate) fn tee(sink: &mut Option<Box<dyn Write + '_>>, output: &str) {
+    if let Some(sink) = sink {
+        let _ = sink.write_all(console::strip_ansi_codes(output).as_bytes());
+        let _ = sink.flush();
+    }
+}
+
+/// The number of rows `output` occupies in a terminal `width` columns wide
+fn wrapped_height(width: usize, output: &str) -> usize {
+    output
+        .lines()
+        .map(|line| {
+            console::measure_text_width(line)
+                .div_ceil(width.max(1))
+                .max(1)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapped_height() {
+        assert_eq!(2, wrapped_height(10, "Title\nshort\n"));
+        assert_eq!(3, wrapped_height(10, "Title\n\x1b[1m0123456789ab\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_tee() {
+        let mut buf = Vec::new();
+        let mut sink: Option<Box<dyn Write + '_>> = Some(Box::new(&mut buf));
+        tee(&mut sink, "\x1b[1mCountry\x1b[0m Germany\n");
+        drop(sink);
+        assert_eq!("Country Germany\n", String::from_utf8(buf).unwrap());
+    }
+
+    #[test]
+    fn test_hyperlink() {
+        assert_eq!(
+            "\x1b]8;;https://docs.rs\x1b\\docs\x1b]8;;\x1b\\",
+            osc8("docs", "https://docs.rs")
+        );
+        // colors are disabled in tests, like when output is piped
+        assert_eq!("docs", hyperlink("docs", Some("https://docs.rs")));
+    }
+}
@@ -4,8 +4,9 @@ use std::borrow::Cow;
 fn init() {
     console::set_colors_enabled(false);
     console::set_colors_enabled_stderr(false);
+    crate::theme::set_ascii(false);
 }
 
-pub fn without_ansi(s: &str) -> Cow<str> {
+pub fn without_ansi(s: &str) -> Cow<'_, str> {
     console::strip_ansi_codes(s)
 }
@@ -1,7 +1,91 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
 use once_cell::sync::Lazy;
 use termcolor::{Color, ColorSpec};
 
-pub(crate) static DEFAULT: Lazy<Theme> = Lazy::new(Theme::default);
+static DEFAULT: Lazy<RwLock<Theme>> = Lazy::new(|| RwLock::new(Theme::default()));
+
+/// Set the theme used by all prompts created afterwards.
+///
+/// Prompts can still override it with their own `theme` builder.
+///
+/// # Example
+///
+/// ```
+/// use demand::Theme;
+///
+/// demand::set_default_theme(Theme::catppuccin());
+/// ```
+pub fn set_default_theme(theme: Theme) {
+    *DEFAULT.write().unwrap_or_else(|e| e.into_inner()) = theme;
+}
+
+/// The theme prompts use when none is set explicitly
+pub(crate) fn default_theme() -> Theme {
+    DEFAULT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+static ASCII: Lazy<AtomicBool> = Lazy::new(|| AtomicBool::new(detect_ascii()));
+
+/// Set whether prompts draw with ASCII characters only.
+///
+/// By default this is on when `TERM=dumb` or the locale is not UTF-8. Call it before any
+/// prompt is created so the default theme picks it up.
+///
+/// # Example
+///
+/// ```
+/// demand::set_ascii(true);
+/// ```
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Whether prompts should avoid Unicode glyphs
+pub(crate) fn ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Pick `unicode`, or its `ascii` fallback in ASCII-safe mode
+pub(crate) fn glyph<'a>(unicode: &'a str, ascii: &'a str) -> &'a str {
+    if self::ascii() {
+        ascii
+    } else {
+        unicode
+    }
+}
+
+/// Spell out the arrows of a help key such as "↑/↓/k/j" in ASCII-safe mode
+pub(crate) fn help_key(key: &str) -> Cow<'_, str> {
+    if ascii() {
+        Cow::Owned(ascii_arrows(key))
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+fn ascii_arrows(key: &str) -> String {
+    key.replace('↑', "up")
+        .replace('↓', "down")
+        .replace('←', "left")
+        .replace('→', "right")
+}
+
+fn detect_ascii() -> bool {
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return true;
+    }
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|locale| !locale.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        })
+}
 
 #[derive(Clone, Debug)]
 pub enum CursorShape {
@@ -39,6 +123,10 @@ pub struct Theme {
     pub selected_prefix: String,
     /// Selected prefix foreground color
     pub selected_prefix_fg: ColorSpec,
+    /// Indeterminate (partially selected) option prefix
+    pub indeterminate_prefix: String,
+    /// Indeterminate prefix foreground color
+    pub indeterminate_prefix_fg: ColorSpec,
     /// Unselected option color
     pub unselected_option: ColorSpec,
     /// Unselected option prefix color
@@ -66,6 +154,8 @@ pub struct Theme {
     pub help_desc: ColorSpec,
     /// Help item separator color
     pub help_sep: ColorSpec,
+    /// Help item separator e.g. " • "
+    pub help_sep_str: String,
 
     /// Focused button
     pub focused_button: ColorSpec,
@@ -74,6 +164,9 @@ pub struct Theme {
 
     /// Error indicator color
     pub error_indicator: ColorSpec,
+
+    /// Option badge color
+    pub badge: ColorSpec,
 }
 
 impl Theme {
@@ -96,11 +189,14 @@ impl Theme {
         Self {
             title: ColorSpec::new(),
             error_indicator: ColorSpec::new(),
+            badge: ColorSpec::new(),
             description: ColorSpec::new(),
             cursor: ColorSpec::new(),
             cursor_str: String::from("❯"),
             selected_prefix: String::from("[•]"),
             selected_prefix_fg: ColorSpec::new(),
+            indeterminate_prefix: String::from("[-]"),
+            indeterminate_prefix_fg: ColorSpec::new(),
             selected_option: ColorSpec::new(),
             unselected_prefix: String::from("[ ]"),
             unselected_prefix_fg: ColorSpec::new(),
@@ -111,6 +207,7 @@ impl Theme {
             help_key: ColorSpec::new(),
             help_desc: ColorSpec::new(),
             help_sep: ColorSpec::new(),
+            help_sep_str: String::from(" • "),
             focused_button,
             blurred_button,
 
@@ -173,12 +270,15 @@ impl Theme {
         Self {
             title,
             error_indicator: make_color(red),
+            badge: make_color(fuchsia),
             description: make_color(Color::Ansi256(243)),
             cursor: make_color(fuchsia),
             cursor_str: String::from("❯"),
 
             selected_prefix: String::from(" ✓"),
             selected_prefix_fg: make_color(Color::Rgb(2, 168, 119)),
+            indeterminate_prefix: String::from(" -"),
+            indeterminate_prefix_fg: make_color(Color::Rgb(2, 168, 119)),
             selected_option: make_color(green),
             unselected_prefix: String::from(" •"),
             unselected_prefix_fg: make_color(Color::Ansi256(243)),
@@ -191,6 +291,7 @@ impl Theme {
             help_key: make_color(Color::Rgb(98, 98, 98)),
             help_desc: make_color(Color::Rgb(74, 74, 74)),
             help_sep: make_color(Color::Rgb(60, 60, 60)),
+            help_sep_str: String::from(" • "),
 
             focused_button,
             blurred_button,
@@ -230,12 +331,15 @@ impl Theme {
         Self {
             title,
             error_indicator: make_color(red),
+            badge: make_color(yellow),
             description: make_color(comment),
             cursor: make_color(yellow),
             cursor_str: String::from("❯"),
 
             selected_prefix: String::from(" [•]"),
             selected_prefix_fg: make_color(green),
+            indeterminate_prefix: String::from(" [-]"),
+            indeterminate_prefix_fg: make_color(green),
             selected_option: make_color(green),
             unselected_prefix: String::from(" [ ]"),
             unselected_prefix_fg: make_color(comment),
@@ -248,6 +352,7 @@ impl Theme {
             help_key: make_color(Color::Rgb(98, 98, 98)),
             help_desc: make_color(Color::Rgb(74, 74, 74)),
             help_sep: make_color(Color::Rgb(60, 60, 60)),
+            help_sep_str: String::from(" • "),
 
             focused_button,
             blurred_button,
@@ -279,12 +384,15 @@ impl Theme {
         Self {
             title,
             error_indicator: make_color(Color::Ansi256(9)),
+            badge: make_color(Color::Ansi256(3)),
             description: make_color(Color::Ansi256(8)),
             cursor: make_color(Color::Ansi256(3)),
             cursor_str: String::from("❯"),
 
             selected_prefix: String::from(" [•]"),
             selected_prefix_fg: make_color(Color::Ansi256(2)),
+            indeterminate_prefix: String::from(" [-]"),
+            indeterminate_prefix_fg: make_color(Color::Ansi256(2)),
             selected_option: make_color(Color::Ansi256(2)),
             unselected_prefix: String::from(" [ ]"),
             unselected_prefix_fg: make_color(Color::Ansi256(7)),
@@ -297,6 +405,7 @@ impl Theme {
             help_key: make_color(Color::Rgb(98, 98, 98)),
             help_desc: make_color(Color::Rgb(74, 74, 74)),
             help_sep: make_color(Color::Rgb(60, 60, 60)),
+            help_sep_str: String::from(" • "),
 
             focused_button,
             blurred_button,
@@ -339,12 +448,15 @@ impl Theme {
         Self {
             title,
             error_indicator: make_color(red),
+            badge: make_color(mauve),
             description: make_color(subtext0),
             cursor: make_color(pink),
             cursor_str: String::from("❯"),
 
             selected_prefix: String::from(" [•]"),
             selected_prefix_fg: make_color(green),
+            indeterminate_prefix: String::from(" [-]"),
+            indeterminate_prefix_fg: make_color(green),
             selected_option: make_color(green),
             unselected_prefix: String::from(" [ ]"),
             unselected_prefix_fg: make_color(text),
@@ -357,6 +469,7 @@ impl Theme {
             help_key: make_color(subtext0),
             help_desc: make_color(overlay1),
             help_sep: make_color(subtext0),
+            help_sep_str: String::from(" • "),
 
             focused_button,
             blurred_button,
@@ -368,6 +481,16 @@ impl Theme {
         }
     }
 
+    /// Replace the Unicode glyphs of this theme with ASCII ones, keeping its colors
+    pub fn ascii(mut self) -> Self {
+        self.cursor_str = String::from(">");
+        self.selected_prefix = String::from("[x]");
+        self.indeterminate_prefix = String::from("[-]");
+        self.unselected_prefix = String::from("[ ]");
+        self.help_sep_str = String::from(" | ");
+        self
+    }
+
     /// Create a new color with foreground color from an RGB value.
     pub fn color_rgb(r: u8, g: u8, b: u8) -> ColorSpec {
         make_color(Color::Rgb(r, g, b))
@@ -381,16 +504,49 @@ impl Theme {
 
 impl Default for Theme {
     fn default() -> Self {
-        if console::colors_enabled_stderr() {
+        let theme = if console::colors_enabled_stderr() {
             Theme::charm()
         } else {
             Theme::new()
+        };
+        if ascii() {
+            theme.ascii()
+        } else {
+            theme
         }
     }
 }
 
+impl<'a> From<&'a Theme> for Cow<'a, Theme> {
+    fn from(theme: &'a Theme) -> Self {
+        Cow::Borrowed(theme)
+    }
+}
+
+impl From<Theme> for Cow<'_, Theme> {
+    fn from(theme: Theme) -> Self {
+        Cow::Owned(theme)
+    }
+}
+
 fn make_color(color: Color) -> ColorSpec {
     let mut spec = ColorSpec::new();
     spec.set_fg(Some(color));
     spec
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii() {
+        let theme = Theme::charm().ascii();
+        assert_eq!(">", theme.cursor_str);
+        assert_eq!("[x]", theme.selected_prefix);
+        assert_eq!("[ ]", theme.unselected_prefix);
+        assert!(theme.help_sep_str.is_ascii());
+        assert_eq!("up/down/k/j", ascii_arrows("↑/↓/k/j"));
+        assert_eq!("left/right/h/l", ascii_arrows("←/→/h/l"));
+    }
+}
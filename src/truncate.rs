@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+
+use crate::theme;
+
+/// Where to cut a label that is too wide to fit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Truncation {
+    /// Keep the start of the label, e.g. "path/to/some/fi…"
+    #[default]
+    End,
+    /// Keep both ends of the label, e.g. "path/to/…/file.rs"
+    ///
+    /// For labels containing `/` the last path component is kept whole when it fits.
+    Middle,
+}
+
+/// Shorten `label` to at most `width` columns
+pub(crate) fn truncate(label: &str, width: usize, truncation: Truncation) -> Cow<'_, str> {
+    if console::measure_text_width(label) <= width {
+        return Cow::Borrowed(label);
+    }
+    let ellipsis = theme::glyph("…", "...");
+    let budget = width.saturating_sub(console::measure_text_width(ellipsis));
+    let (head, tail) = match truncation {
+        Truncation::End => (take_start(label, budget), ""),
+        Truncation::Middle => {
+            let name = label.rfind('/').map(|i| &label[i..]).unwrap_or("");
+            let name_width = console::measure_text_width(name);
+            if !name.is_empty() && name_width < budget {
+                // cut the directories at a separator so no partial component is left
+                let head = take_start(label, budget - name_width);
+                let head = head.rfind('/').map(|i| &head[..=i]).unwrap_or(head);
+                (head, name)
+            } else {
+                let tail = take_end(label, budget / 2);
+                (
+                    take_start(label, budget - console::measure_text_width(tail)),
+                    tail,
+                )
+            }
+        }
+    };
+    Cow::Owned(format!("{head}{ellipsis}{tail}"))
+}
+
+/// The longest prefix of `s` at most `width` columns wide
+fn take_start(s: &str, width: usize) -> &str {
+    let mut col = 0;
+    for (i, c) in s.char_indices() {
+        col += console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+        if col > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+/// The longest suffix of `s` at most `width` columns wide
+fn take_end(s: &str, width: usize) -> &str {
+    let mut col = 0;
+    for (i, c) in s.char_indices().rev() {
+        col += console::measure_text_width(c.encode_utf8(&mut [0; 4]));
+        if col > width {
+            return &s[i + c.len_utf8()..];
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        let path = "path/to/some/deeply/nested/file.rs";
+        assert_eq!(path, truncate(path, 40, Truncation::Middle));
+        assert_eq!("path/to/some/deep…", truncate(path, 18, Truncation::End));
+        assert_eq!("path/to/…/file.rs", truncate(path, 18, Truncation::Middle));
+        assert_eq!(
+            "an_ex…_name",
+            truncate("an_extremely_long_name", 11, Truncation::Middle)
+        );
+        assert_eq!(
+            "path/…me.rs",
+            truncate("path/a_very_long_name.rs", 11, Truncation::Middle)
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Ready-made validators for `Input`
+//!
+//! # Example
+//! ```rust
+//! use demand::validators::{And, Length, NonEmpty};
+//! use demand::Input;
+//!
+//! let input = Input::new("Username").validator(And(NonEmpty, Length { min: 3, max: 16 }));
+//! ```
+
+use crate::InputValidator;
+
+/// Rejects values that are empty or only whitespace
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NonEmpty;
+
+impl InputValidator for NonEmpty {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        match value.trim().is_empty() {
+            true => Err("A value is required".to_string()),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Accepts addresses of the form `name@example.com`
+///
+/// Only the overall shape is checked, not whether the address exists.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Email;
+
+impl InputValidator for Email {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        let valid = match value.trim().split_once('@') {
+            Some((name, domain)) => {
+                !name.is_empty()
+                    && !domain.contains('@')
+                    && domain.split('.').count() > 1
+                    && domain.split('.').all(|part| !part.is_empty())
+                    && !value.trim().contains(char::is_whitespace)
+            }
+            None => false,
+        };
+        match valid {
+            true => Ok(()),
+            false => Err("Enter a valid email address".to_string()),
+        }
+    }
+}
+
+/// Accepts absolute URLs such as `https://example.com/path`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Url;
+
+impl InputValidator for Url {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        let valid = match value.trim().split_once("://") {
+            Some((scheme, rest)) => {
+                scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+                    && !rest.is_empty()
+                    && !rest.starts_with('/')
+                    && !rest.contains(char::is_whitespace)
+            }
+            None => false,
+        };
+        match valid {
+            true => Ok(()),
+            false => Err("Enter a valid URL".to_string()),
+        }
+    }
+}
+
+/// Accepts whole numbers from the first bound to the second, inclusive
+#[derive(Clone, Copy, Debug)]
+pub struct IntRange(pub i64, pub i64);
+
+impl InputValidator for IntRange {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        let Ok(n) = value.trim().parse::<i64>() else {
+            return Err("Enter a whole number".to_string());
+        };
+        match (self.0..=self.1).contains(&n) {
+            true => Ok(()),
+            false => Err(format!("Enter a number from {} to {}", self.0, self.1)),
+        }
+    }
+}
+
+/// Accepts values with between `min` and `max` characters, inclusive
+#[derive(Clone, Copy, Debug)]
+pub struct Length {
+    /// The fewest characters allowed
+    pub min: usize,
+    /// The most characters allowed
+    pub max: usize,
+}
+
+impl InputValidator for Length {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        let len = value.chars().count();
+        if len < self.min {
+            Err(format!("Enter at least {} characters", self.min))
+        } else if len > self.max {
+            Err(format!("Enter at most {} characters", self.max))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Accepts values both validators accept, reporting the first failure
+#[derive(Clone, Copy, Debug)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: InputValidator, B: InputValidator> InputValidator for And<A, B> {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        self.0.validate(value)?;
+        self.1.validate(value)
+    }
+}
+
+/// Accepts values either validator accepts, reporting the first validator's message otherwise
+#[derive(Clone, Copy, Debug)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: InputValidator, B: InputValidator> InputValidator for Or<A, B> {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        match self.0.validate(value) {
+            Ok(()) => Ok(()),
+            Err(err) => self.1.validate(value).map_err(|_| err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_empty() {
+        assert_eq!(Ok(()), NonEmpty.validate("a"));
+        assert_eq!(
+            Err("A value is required".to_string()),
+            NonEmpty.validate(" ")
+        );
+    }
+
+    #[test]
+    fn test_email() {
+        assert_eq!(Ok(()), Email.validate("jdx@example.com"));
+        for value in [
+            "jdx",
+            "@example.com",
+            "jdx@example",
+            "jdx@example..com",
+            "j dx@a.b",
+        ] {
+            assert!(Email.validate(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_url() {
+        assert_eq!(Ok(()), Url.validate("https://example.com/path?q=1"));
+        for value in [
+            "example.com",
+            "https://",
+            "1http://example.com",
+            "https:///path",
+        ] {
+            assert!(Url.validate(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_int_range() {
+        assert_eq!(Ok(()), IntRange(1, 10).validate("10"));
+        assert_eq!(
+            Err("Enter a number from 1 to 10".to_string()),
+            IntRange(1, 10).validate("11")
+        );
+        assert_eq!(
+            Err("Enter a whole number".to_string()),
+            IntRange(1, 10).validate("1.5")
+        );
+    }
+
+    #[test]
+    fn test_length() {
+        let length = Length { min: 2, max: 3 };
+        assert_eq!(Ok(()), length.validate("añb"));
+        assert_eq!(
+            Err("Enter at least 2 characters".to_string()),
+            length.validate("a")
+        );
+        assert_eq!(
+            Err("Enter at most 3 characters".to_string()),
+            length.validate("abcd")
+        );
+    }
+
+    #[test]
+    fn test_combinators() {
+        let port = And(NonEmpty, IntRange(1, 65535));
+        assert_eq!(Err("A value is required".to_string()), port.validate(""));
+        assert_eq!(Ok(()), port.validate("8080"));
+
+        let contact = Or(Email, Url);
+        assert_eq!(Ok(()), contact.validate("https://example.com"));
+        assert_eq!(
+            Err("Enter a valid email address".to_string()),
+            contact.validate("nope")
+        );
+    }
+}